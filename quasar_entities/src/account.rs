@@ -2,13 +2,32 @@ use std::collections::HashMap;
 
 use thiserror::Error;
 
-use async_graphql::{dataloader::Loader, ComplexObject, Context};
-use base64::{engine::general_purpose, Engine};
-use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, Set};
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context,
+};
+use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, Order, Set};
 use std::sync::Arc;
-use stellar_node_entities::accounts;
+use stellar_strkey::ed25519::PublicKey as StrkeyPublicKey;
+use stellar_xdr::curr::{
+    AccountEntry, AccountEntryExt, AccountEntryExtensionV1, AccountEntryExtensionV1Ext,
+    AccountEntryExtensionV2, AccountEntryExtensionV2Ext, AccountFlags, AccountId as XdrAccountId,
+    LedgerEntry, LedgerEntryData, LedgerEntryExt, PublicKey, SponsorshipDescriptor,
+};
 
-use crate::{ledger, transaction, QuasarDataLoader};
+use crate::{
+    account_data::{self, AccountDataEntries},
+    account_history::{self, AccountHistoryEntries, AccountSnapshot},
+    account_signer::{self, AccountSigners},
+    children::{ChildRelation, Children},
+    filter::{OperationFilter, TransactionFilter},
+    ledger::{self, LedgerSequence},
+    operation::{self, AccountOperations},
+    pagination::Pagination,
+    sort::{sort_columns, OperationSort, TransactionSort},
+    transaction::{self, AccountTransactions},
+    QuasarDataLoader,
+};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, async_graphql::SimpleObject)]
 #[sea_orm(table_name = "accounts")]
@@ -29,6 +48,13 @@ pub struct Model {
     pub threshold_medium: i16,
     pub threshold_high: i16,
     pub last_modified: i32,
+    pub flags: i32,
+    pub sponsor: Option<String>,
+    pub number_of_sponsored: i32,
+    pub number_of_sponsoring: i32,
+    pub sequence_ledger: Option<i32>,
+    pub sequence_time: Option<i64>,
+    pub deleted_in_ledger: Option<i32>,
     pub created_at: DateTimeWithTimeZone,
 }
 
@@ -46,6 +72,24 @@ pub enum Relation {
         from = "Column::LastModified"
     )]
     Ledger,
+    #[sea_orm(
+        has_many = "super::account_data::Entity",
+        to = "super::account_data::Column::AccountId",
+        from = "Column::Id"
+    )]
+    AccountData,
+    #[sea_orm(
+        has_many = "super::account_signer::Entity",
+        to = "super::account_signer::Column::AccountId",
+        from = "Column::Id"
+    )]
+    AccountSigner,
+    #[sea_orm(
+        has_many = "super::account_history::Entity",
+        to = "super::account_history::Column::AccountId",
+        from = "Column::Id"
+    )]
+    AccountHistory,
 }
 
 impl Related<super::transaction::Entity> for Entity {
@@ -60,25 +104,169 @@ impl Related<super::ledger::Entity> for Entity {
     }
 }
 
+impl Related<super::account_data::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AccountData.def()
+    }
+}
+
+impl Related<super::account_signer::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AccountSigner.def()
+    }
+}
+
+impl Related<super::account_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AccountHistory.def()
+    }
+}
+
+impl Related<super::operation::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::transaction::Relation::Operation.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(Relation::Transaction.def())
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
 #[ComplexObject]
 impl Model {
+    /// Transactions submitted by this account, latest first by default
     pub async fn transactions<'ctx>(
         &self,
         ctx: &Context<'ctx>,
-    ) -> Result<Vec<transaction::Model>, DbErr> {
-        let database = ctx
-            .data::<DatabaseConnection>()
-            .expect("DatabaseConnection missing from GraphQL context");
-        self.find_related(transaction::Entity).all(database).await
+        filter: Option<TransactionFilter>,
+        sort: Option<Vec<TransactionSort>>,
+        pagination: Option<Pagination>,
+    ) -> async_graphql::Result<Vec<transaction::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let query = AccountTransactions::query();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+        let order = sort_columns(
+            sort,
+            &[
+                (transaction::Column::LedgerSequence, Order::Desc),
+                (transaction::Column::ApplicationOrder, Order::Desc),
+            ],
+        );
+        let key = Children::<AccountTransactions>::new(self.id.clone(), query, order, pagination)?;
+        Ok(loader.load_one(key).await?.unwrap_or_default())
+    }
+
+    /// Operations of the transactions submitted by this account, latest first by default
+    pub async fn operations<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        filter: Option<OperationFilter>,
+        sort: Option<Vec<OperationSort>>,
+        pagination: Option<Pagination>,
+    ) -> async_graphql::Result<Vec<operation::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let query = AccountOperations::query();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+        let order = sort_columns(sort, &[(operation::Column::Id, Order::Desc)]);
+        let key = Children::<AccountOperations>::new(self.id.clone(), query, order, pagination)?;
+        Ok(loader.load_one(key).await?.unwrap_or_default())
+    }
+
+    pub async fn ledger<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Option<ledger::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        Ok(loader.load_one(LedgerSequence(self.last_modified)).await?)
+    }
+
+    pub async fn data<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Vec<account_data::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let data = loader.load_one(AccountDataEntries(self.id.clone())).await?;
+        Ok(data.unwrap_or_default())
     }
 
-    pub async fn ledger<'ctx>(&self, ctx: &Context<'ctx>) -> Result<Option<ledger::Model>, DbErr> {
-        let database = ctx
-            .data::<DatabaseConnection>()
-            .expect("DatabaseConnection missing from GraphQL context");
-        self.find_related(ledger::Entity).one(database).await
+    pub async fn signers<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Vec<account_signer::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let signers = loader.load_one(AccountSigners(self.id.clone())).await?;
+        Ok(signers.unwrap_or_default())
+    }
+
+    /// Balance of the account at the end of the given ledger
+    pub async fn balance_at<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        ledger: i32,
+    ) -> async_graphql::Result<Option<i64>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let snapshot = loader
+            .load_one(AccountSnapshot(self.id.clone(), ledger))
+            .await?;
+        Ok(snapshot.map(|snapshot| snapshot.balance))
+    }
+
+    /// Snapshots of the account, latest first
+    pub async fn history<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        pagination: Option<Pagination>,
+    ) -> async_graphql::Result<Vec<account_history::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let order = vec![
+            (account_history::Column::LedgerSequence, Order::Desc),
+            (account_history::Column::Id, Order::Desc),
+        ];
+        let key = Children::<AccountHistoryEntries>::new(
+            self.id.clone(),
+            AccountHistoryEntries::query(),
+            order,
+            pagination,
+        )?;
+        Ok(loader.load_one(key).await?.unwrap_or_default())
+    }
+
+    pub async fn auth_required(&self) -> bool {
+        self.has_flag(AccountFlags::RequiredFlag)
+    }
+
+    pub async fn auth_revocable(&self) -> bool {
+        self.has_flag(AccountFlags::RevocableFlag)
+    }
+
+    pub async fn auth_immutable(&self) -> bool {
+        self.has_flag(AccountFlags::ImmutableFlag)
+    }
+
+    pub async fn auth_clawback_enabled(&self) -> bool {
+        self.has_flag(AccountFlags::ClawbackEnabledFlag)
+    }
+}
+
+impl Model {
+    fn has_flag(&self, flag: AccountFlags) -> bool {
+        self.flags & flag as i32 != 0
     }
 }
 
@@ -88,53 +276,96 @@ pub enum AccountError {
     Base64Error(#[from] base64::DecodeError),
     #[error("UTF-8 decoding error: {0}")]
     FromUtf8Error(#[from] std::string::FromUtf8Error),
-    #[error("Invalid account thresholds")]
-    InvalidThresholds,
     #[error("Base64 decoding error: {0}")]
     DatabaseError(#[from] Arc<sea_orm::DbErr>),
+    #[error("XDR decoding error: {0}")]
+    XdrError(#[from] Arc<stellar_xdr::curr::Error>),
+    #[error("Unexpected ledger entry type")]
+    UnexpectedLedgerEntry,
 }
 
-impl TryFrom<accounts::Model> for ActiveModel {
+impl TryFrom<LedgerEntry> for ActiveModel {
     type Error = AccountError;
 
-    fn try_from(accounts: accounts::Model) -> Result<Self, Self::Error> {
-        let home_domain =
-            String::from_utf8(general_purpose::STANDARD.decode(accounts.homedomain)?)?;
-
-        let thresholds_base64_decoded = general_purpose::STANDARD.decode(accounts.thresholds)?;
-
-        let (master_weight, threshold_low, threshold_medium, threshold_high) = if let [master_weight, threshold_low, threshold_medium, threshold_high] =
-            thresholds_base64_decoded.as_slice()
-        {
-            (
-                master_weight,
-                threshold_low,
-                threshold_medium,
-                threshold_high,
-            )
-        } else {
-            return Err(AccountError::InvalidThresholds);
+    fn try_from(entry: LedgerEntry) -> Result<Self, Self::Error> {
+        let sponsor = ledger_entry_sponsor(&entry);
+        let account = match entry.data {
+            LedgerEntryData::Account(account) => account,
+            _ => return Err(AccountError::UnexpectedLedgerEntry),
         };
 
+        let home_domain = String::from_utf8(account.home_domain.0.to_vec())?;
+
+        let [master_weight, threshold_low, threshold_medium, threshold_high] = account.thresholds.0;
+
+        let liabilities = match &account.ext {
+            AccountEntryExt::V1(v1) => Some(&v1.liabilities),
+            AccountEntryExt::V0 => None,
+        };
+
+        let extension = account_extension_v2(&account);
+        let sequence_extension = extension.and_then(|extension| match &extension.ext {
+            AccountEntryExtensionV2Ext::V3(v3) => Some(v3),
+            AccountEntryExtensionV2Ext::V0 => None,
+        });
+
         Ok(Self {
-            id: Set(accounts.accountid),
-            balance: Set(accounts.balance),
-            buying_liabilities: Set(accounts.buyingliabilities),
-            selling_liabilities: Set(accounts.sellingliabilities),
-            sequence_number: Set(accounts.seqnum),
-            number_of_subentries: Set(accounts.numsubentries),
-            inflation_destination: Set(accounts.inflationdest),
+            id: Set(account_id_to_string(&account.account_id)),
+            balance: Set(account.balance),
+            buying_liabilities: Set(liabilities.map(|liabilities| liabilities.buying)),
+            selling_liabilities: Set(liabilities.map(|liabilities| liabilities.selling)),
+            sequence_number: Set(account.seq_num.0),
+            number_of_subentries: Set(account.num_sub_entries as i32),
+            inflation_destination: Set(account.inflation_dest.as_ref().map(account_id_to_string)),
             home_domain: Set(home_domain),
-            master_weight: Set(*master_weight as i16),
-            threshold_low: Set(*threshold_low as i16),
-            threshold_medium: Set(*threshold_medium as i16),
-            threshold_high: Set(*threshold_high as i16),
-            last_modified: Set(accounts.lastmodified),
+            master_weight: Set(master_weight as i16),
+            threshold_low: Set(threshold_low as i16),
+            threshold_medium: Set(threshold_medium as i16),
+            threshold_high: Set(threshold_high as i16),
+            last_modified: Set(entry.last_modified_ledger_seq as i32),
+            flags: Set(account.flags as i32),
+            sponsor: Set(sponsor),
+            number_of_sponsored: Set(
+                extension.map_or(0, |extension| extension.num_sponsored as i32)
+            ),
+            number_of_sponsoring: Set(
+                extension.map_or(0, |extension| extension.num_sponsoring as i32)
+            ),
+            sequence_ledger: Set(sequence_extension.map(|v3| v3.seq_ledger as i32)),
+            sequence_time: Set(sequence_extension.map(|v3| v3.seq_time.0 as i64)),
+            deleted_in_ledger: Set(None),
             created_at: NotSet,
         })
     }
 }
 
+/// Returns the V2 extension of an account entry, which holds the sponsorship counters
+pub fn account_extension_v2(account: &AccountEntry) -> Option<&AccountEntryExtensionV2> {
+    match &account.ext {
+        AccountEntryExt::V1(AccountEntryExtensionV1 {
+            ext: AccountEntryExtensionV1Ext::V2(v2),
+            ..
+        }) => Some(v2),
+        _ => None,
+    }
+}
+
+pub fn ledger_entry_sponsor(entry: &LedgerEntry) -> Option<String> {
+    match &entry.ext {
+        LedgerEntryExt::V1(v1) => sponsor_to_string(&v1.sponsoring_id),
+        LedgerEntryExt::V0 => None,
+    }
+}
+
+pub fn sponsor_to_string(sponsor: &SponsorshipDescriptor) -> Option<String> {
+    sponsor.0.as_ref().map(account_id_to_string)
+}
+
+pub fn account_id_to_string(account_id: &XdrAccountId) -> String {
+    let XdrAccountId(PublicKey::PublicKeyTypeEd25519(key)) = account_id;
+    StrkeyPublicKey(key.0).to_string()
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct AccountId(pub String);
 
@@ -163,3 +394,27 @@ impl Loader<AccountId> for QuasarDataLoader {
             .collect())
     }
 }
+
+/// Accounts last modified in a ledger, keyed by ledger sequence
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LedgerAccounts(pub i32);
+
+#[async_trait::async_trait]
+impl Loader<LedgerAccounts> for QuasarDataLoader {
+    type Value = Vec<Model>;
+    type Error = Arc<DbErr>;
+
+    async fn load(
+        &self,
+        keys: &[LedgerAccounts],
+    ) -> Result<HashMap<LedgerAccounts, Self::Value>, Self::Error> {
+        let mut condition = Condition::any();
+
+        for LedgerAccounts(key) in keys {
+            condition = condition.add(Column::LastModified.eq(*key));
+        }
+        let query = Entity::find().filter(condition);
+        self.load_grouped(query, |model| LedgerAccounts(model.last_modified))
+            .await
+    }
+}
@@ -0,0 +1,107 @@
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context,
+};
+use base64::{engine::general_purpose, Engine};
+use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, QueryOrder, Set};
+use std::{collections::HashMap, sync::Arc};
+use stellar_xdr::curr::{LedgerEntry, LedgerEntryData};
+
+use crate::{
+    account::{self, account_id_to_string, AccountError, AccountId},
+    QuasarDataLoader,
+};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, async_graphql::SimpleObject)]
+#[sea_orm(table_name = "account_data")]
+#[graphql(complex)]
+#[graphql(name = "AccountData")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub account_id: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub name: String,
+    /// Decoded value, `None` when the value is not valid UTF-8
+    pub value: Option<String>,
+    /// Base64 encoded value, as stored on the ledger
+    pub raw_value: String,
+    pub last_modified: i32,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::account::Entity",
+        from = "Column::AccountId",
+        to = "super::account::Column::Id"
+    )]
+    Account,
+}
+
+impl Related<super::account::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Account.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[ComplexObject]
+impl Model {
+    pub async fn account<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Option<account::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        Ok(loader.load_one(AccountId(self.account_id.clone())).await?)
+    }
+}
+
+impl TryFrom<LedgerEntry> for ActiveModel {
+    type Error = AccountError;
+
+    fn try_from(entry: LedgerEntry) -> Result<Self, Self::Error> {
+        let data = match entry.data {
+            LedgerEntryData::Data(data) => data,
+            _ => return Err(AccountError::UnexpectedLedgerEntry),
+        };
+
+        let value = data.data_value.0.to_vec();
+
+        Ok(Self {
+            account_id: Set(account_id_to_string(&data.account_id)),
+            name: Set(String::from_utf8(data.data_name.0.to_vec())?),
+            raw_value: Set(general_purpose::STANDARD.encode(&value)),
+            value: Set(String::from_utf8(value).ok()),
+            last_modified: Set(entry.last_modified_ledger_seq as i32),
+            created_at: NotSet,
+        })
+    }
+}
+
+/// Data entries of an account, keyed by account id
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AccountDataEntries(pub String);
+
+#[async_trait::async_trait]
+impl Loader<AccountDataEntries> for QuasarDataLoader {
+    type Value = Vec<Model>;
+    type Error = Arc<DbErr>;
+
+    async fn load(
+        &self,
+        keys: &[AccountDataEntries],
+    ) -> Result<HashMap<AccountDataEntries, Self::Value>, Self::Error> {
+        let mut condition = Condition::any();
+
+        for AccountDataEntries(key) in keys {
+            condition = condition.add(Column::AccountId.eq(key.clone()));
+        }
+        let query = Entity::find().filter(condition).order_by_asc(Column::Name);
+        self.load_grouped(query, |model| AccountDataEntries(model.account_id.clone()))
+            .await
+    }
+}
@@ -0,0 +1,206 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context,
+};
+use sea_orm::{entity::prelude::*, ActiveValue::NotSet, QueryOrder, QuerySelect, Set};
+
+use crate::{
+    account,
+    children::ChildRelation,
+    ledger::{self, LedgerSequence},
+    transaction::{self, TransactionId},
+    QuasarDataLoader,
+};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, async_graphql::SimpleObject)]
+#[sea_orm(table_name = "account_history")]
+#[graphql(complex)]
+#[graphql(name = "AccountHistory")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub account_id: String,
+    pub ledger_sequence: i32,
+    pub transaction_id: Option<String>,
+    pub balance: i64,
+    pub buying_liabilities: Option<i64>,
+    pub selling_liabilities: Option<i64>,
+    pub sequence_number: i64,
+    pub number_of_subentries: i32,
+    pub deleted: bool,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::account::Entity",
+        from = "Column::AccountId",
+        to = "super::account::Column::Id"
+    )]
+    Account,
+    #[sea_orm(
+        belongs_to = "super::ledger::Entity",
+        from = "Column::LedgerSequence",
+        to = "super::ledger::Column::Sequence"
+    )]
+    Ledger,
+    #[sea_orm(
+        belongs_to = "super::transaction::Entity",
+        from = "Column::TransactionId",
+        to = "super::transaction::Column::Id"
+    )]
+    Transaction,
+}
+
+impl Related<super::account::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Account.def()
+    }
+}
+
+impl Related<super::ledger::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Ledger.def()
+    }
+}
+
+impl Related<super::transaction::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Transaction.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[ComplexObject]
+impl Model {
+    pub async fn ledger<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Option<ledger::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        Ok(loader
+            .load_one(LedgerSequence(self.ledger_sequence))
+            .await?)
+    }
+
+    pub async fn transaction<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Option<transaction::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        match &self.transaction_id {
+            Some(id) => Ok(loader.load_one(TransactionId(id.clone())).await?),
+            None => Ok(None),
+        }
+    }
+}
+
+impl ActiveModel {
+    /// Snapshot of an account state written to the ledger
+    pub fn from_account(
+        account: &account::ActiveModel,
+        ledger_sequence: i32,
+        transaction_id: Option<String>,
+    ) -> Self {
+        Self {
+            id: NotSet,
+            account_id: account.id.clone(),
+            ledger_sequence: Set(ledger_sequence),
+            transaction_id: Set(transaction_id),
+            balance: account.balance.clone(),
+            buying_liabilities: account.buying_liabilities.clone(),
+            selling_liabilities: account.selling_liabilities.clone(),
+            sequence_number: account.sequence_number.clone(),
+            number_of_subentries: account.number_of_subentries.clone(),
+            deleted: Set(false),
+            created_at: NotSet,
+        }
+    }
+
+    /// Snapshot of an account removed from the ledger, its balance having been merged
+    pub fn from_deleted_account(
+        account_id: String,
+        sequence_number: i64,
+        ledger_sequence: i32,
+        transaction_id: Option<String>,
+    ) -> Self {
+        Self {
+            id: NotSet,
+            account_id: Set(account_id),
+            ledger_sequence: Set(ledger_sequence),
+            transaction_id: Set(transaction_id),
+            balance: Set(0),
+            buying_liabilities: Set(None),
+            selling_liabilities: Set(None),
+            sequence_number: Set(sequence_number),
+            number_of_subentries: Set(0),
+            deleted: Set(true),
+            created_at: NotSet,
+        }
+    }
+}
+
+/// History of an account, keyed by account id
+pub struct AccountHistoryEntries;
+
+impl ChildRelation for AccountHistoryEntries {
+    type Entity = Entity;
+    type Holder = Entity;
+    type Parent = String;
+
+    fn query() -> Select<Entity> {
+        Entity::find()
+    }
+
+    fn parent() -> Column {
+        Column::AccountId
+    }
+}
+
+/// Latest snapshot of an account at the end of a ledger, keyed by account id
+/// and ledger sequence
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AccountSnapshot(pub String, pub i32);
+
+#[async_trait::async_trait]
+impl Loader<AccountSnapshot> for QuasarDataLoader {
+    type Value = Model;
+    type Error = Arc<DbErr>;
+
+    async fn load(
+        &self,
+        keys: &[AccountSnapshot],
+    ) -> Result<HashMap<AccountSnapshot, Self::Value>, Self::Error> {
+        let mut ledgers: HashMap<i32, Vec<String>> = HashMap::new();
+        for AccountSnapshot(account_id, ledger) in keys {
+            ledgers.entry(*ledger).or_default().push(account_id.clone());
+        }
+
+        let mut snapshots = HashMap::new();
+        for (ledger, account_ids) in ledgers {
+            let models = Entity::find()
+                .filter(Column::AccountId.is_in(account_ids))
+                .filter(Column::LedgerSequence.lte(ledger))
+                .distinct_on([Column::AccountId])
+                .order_by_asc(Column::AccountId)
+                .order_by_desc(Column::LedgerSequence)
+                .order_by_desc(Column::Id)
+                .all(&self.pool)
+                .await
+                .map_err(Arc::new)?;
+
+            for model in models {
+                snapshots.insert(AccountSnapshot(model.account_id.clone(), ledger), model);
+            }
+        }
+        Ok(snapshots)
+    }
+}
@@ -0,0 +1,133 @@
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context,
+};
+use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, QueryOrder, Set};
+use std::{collections::HashMap, sync::Arc};
+use stellar_strkey::{
+    ed25519::{PublicKey, SignedPayload},
+    HashX, PreAuthTx,
+};
+use stellar_xdr::curr::{LedgerEntry, LedgerEntryData, SignerKey};
+
+use crate::{
+    account::{
+        self, account_extension_v2, account_id_to_string, sponsor_to_string, AccountError,
+        AccountId,
+    },
+    QuasarDataLoader,
+};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, async_graphql::SimpleObject)]
+#[sea_orm(table_name = "account_signers")]
+#[graphql(complex)]
+#[graphql(name = "AccountSigners")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub account_id: String,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub signer: String,
+    pub r#type: String,
+    pub weight: i32,
+    pub sponsor: Option<String>,
+    pub last_modified: i32,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::account::Entity",
+        from = "Column::AccountId",
+        to = "super::account::Column::Id"
+    )]
+    Account,
+}
+
+impl Related<super::account::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Account.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[ComplexObject]
+impl Model {
+    pub async fn account<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Option<account::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        Ok(loader.load_one(AccountId(self.account_id.clone())).await?)
+    }
+}
+
+/// Decodes the additional signers of an account, the master key is not included
+pub fn signers_from_account(entry: &LedgerEntry) -> Result<Vec<ActiveModel>, AccountError> {
+    let account = match &entry.data {
+        LedgerEntryData::Account(account) => account,
+        _ => return Err(AccountError::UnexpectedLedgerEntry),
+    };
+
+    let account_id = account_id_to_string(&account.account_id);
+    let sponsors = account_extension_v2(account)
+        .map(|extension| extension.signer_sponsoring_i_ds.to_vec())
+        .unwrap_or_default();
+
+    Ok(account
+        .signers
+        .iter()
+        .enumerate()
+        .map(|(index, signer)| ActiveModel {
+            account_id: Set(account_id.clone()),
+            signer: Set(signer_key_to_string(&signer.key)),
+            r#type: Set(signer.key.name().to_string()),
+            weight: Set(signer.weight as i32),
+            sponsor: Set(sponsors.get(index).and_then(sponsor_to_string)),
+            last_modified: Set(entry.last_modified_ledger_seq as i32),
+            created_at: NotSet,
+        })
+        .collect())
+}
+
+fn signer_key_to_string(key: &SignerKey) -> String {
+    match key {
+        SignerKey::Ed25519(key) => PublicKey(key.0).to_string(),
+        SignerKey::PreAuthTx(hash) => PreAuthTx(hash.0).to_string(),
+        SignerKey::HashX(hash) => HashX(hash.0).to_string(),
+        SignerKey::Ed25519SignedPayload(signed_payload) => SignedPayload {
+            ed25519: signed_payload.ed25519.0,
+            payload: signed_payload.payload.to_vec(),
+        }
+        .to_string(),
+    }
+}
+
+/// Signers of an account, keyed by account id
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AccountSigners(pub String);
+
+#[async_trait::async_trait]
+impl Loader<AccountSigners> for QuasarDataLoader {
+    type Value = Vec<Model>;
+    type Error = Arc<DbErr>;
+
+    async fn load(
+        &self,
+        keys: &[AccountSigners],
+    ) -> Result<HashMap<AccountSigners, Self::Value>, Self::Error> {
+        let mut condition = Condition::any();
+
+        for AccountSigners(key) in keys {
+            condition = condition.add(Column::AccountId.eq(key.clone()));
+        }
+        let query = Entity::find()
+            .filter(condition)
+            .order_by_desc(Column::Weight);
+        self.load_grouped(query, |model| AccountSigners(model.account_id.clone()))
+            .await
+    }
+}
@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "api_keys")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// Name of the client, used as the label of the API metrics
+    #[sea_orm(unique)]
+    pub name: String,
+    /// Hex encoded SHA-256 of the key, the key itself is only shown once
+    #[sea_orm(unique)]
+    pub key_hash: String,
+    /// Requests allowed per minute
+    pub rate_limit: i32,
+    /// Complexity of the GraphQL queries allowed per minute, unlimited when `None`
+    pub complexity_budget: Option<i32>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,19 @@
+use sea_orm::entity::prelude::*;
+
+/// Job rewriting rows ingested before a change of the ingestion, registered
+/// by the migration introducing the change
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "backfills")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub name: String,
+    /// Id or ledger sequence up to which the rows were rewritten
+    pub position: i64,
+    /// Last id or ledger sequence to rewrite
+    pub end_position: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,213 @@
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use async_graphql::dataloader::Loader;
+use sea_orm::{
+    sea_query::{Alias, Asterisk, Expr, Query},
+    ColumnTrait, ConnectionTrait, DbBackend, DbErr, EntityName, EntityTrait, FromQueryResult,
+    IdenStatic, Order, QueryFilter, QueryTrait, Select, TryGetable, Value,
+};
+
+use crate::{
+    pagination::{Pagination, DEFAULT_PER_PAGE},
+    QuasarDataLoader,
+};
+
+/// Relation listed by a nested resolver, from a parent to its children
+pub trait ChildRelation: Send + Sync + 'static {
+    /// Entity of the children
+    type Entity: EntityTrait;
+    /// Entity holding the key of the parent, the children or a table joined
+    /// by [`ChildRelation::query`]
+    type Holder: EntityTrait;
+    type Parent: Clone + Eq + Hash + Into<Value> + TryGetable + Send + Sync + 'static;
+
+    /// Query of the children, before filtering
+    fn query() -> Select<Self::Entity>;
+
+    /// Column holding the key of the parent
+    fn parent() -> <Self::Holder as EntityTrait>::Column;
+}
+
+/// Filtered, sorted and paginated list of children, shared by every parent
+/// resolved with the same arguments
+struct ChildList<E: EntityTrait> {
+    query: Select<E>,
+    order: Vec<(E::Column, Order)>,
+    offset: u64,
+    limit: u64,
+    /// Identifies the arguments of the list, parents listed with the same
+    /// arguments are loaded together
+    identity: String,
+}
+
+/// Key of the children of a parent, each parent gets its own page of children
+pub struct Children<R: ChildRelation> {
+    pub parent: R::Parent,
+    list: Arc<ChildList<R::Entity>>,
+}
+
+impl<R: ChildRelation> Children<R> {
+    /// Children of the parent matching the query, in the given order, on the
+    /// requested page or on the first `DEFAULT_PER_PAGE` rows
+    pub fn new(
+        parent: R::Parent,
+        query: Select<R::Entity>,
+        order: Vec<(<R::Entity as EntityTrait>::Column, Order)>,
+        pagination: Option<Pagination>,
+    ) -> async_graphql::Result<Self> {
+        let (offset, limit) = match pagination {
+            Some(pagination) => pagination.bounds()?,
+            None => (0, DEFAULT_PER_PAGE),
+        };
+
+        let identity = format!(
+            "{} ORDER BY {} OFFSET {} LIMIT {}",
+            query.build(DbBackend::Postgres),
+            order_by(&order),
+            offset,
+            limit
+        );
+
+        Ok(Self {
+            parent,
+            list: Arc::new(ChildList {
+                query,
+                order,
+                offset,
+                limit,
+                identity,
+            }),
+        })
+    }
+}
+
+impl<R: ChildRelation> Clone for Children<R> {
+    fn clone(&self) -> Self {
+        Self {
+            parent: self.parent.clone(),
+            list: self.list.clone(),
+        }
+    }
+}
+
+impl<R: ChildRelation> PartialEq for Children<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.parent == other.parent && self.list.identity == other.list.identity
+    }
+}
+
+impl<R: ChildRelation> Eq for Children<R> {}
+
+impl<R: ChildRelation> Hash for Children<R> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.parent.hash(state);
+        self.list.identity.hash(state);
+    }
+}
+
+fn order_by<C: ColumnTrait>(order: &[(C, Order)]) -> String {
+    order
+        .iter()
+        .map(|(column, order)| {
+            let direction = match order {
+                Order::Desc => "DESC",
+                _ => "ASC",
+            };
+            format!(
+                "\"{}\".\"{}\" {}",
+                column.entity_name().to_string(),
+                column.as_str(),
+                direction
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[async_trait::async_trait]
+impl<R> Loader<Children<R>> for QuasarDataLoader
+where
+    R: ChildRelation,
+    <R::Entity as EntityTrait>::Model: Sync,
+{
+    type Value = Vec<<R::Entity as EntityTrait>::Model>;
+    type Error = Arc<DbErr>;
+
+    async fn load(
+        &self,
+        keys: &[Children<R>],
+    ) -> Result<HashMap<Children<R>, Self::Value>, Self::Error> {
+        let mut lists: HashMap<&str, Vec<&Children<R>>> = HashMap::new();
+        for key in keys {
+            lists.entry(&key.list.identity).or_default().push(key);
+        }
+
+        let mut children = HashMap::new();
+        for keys in lists.into_values() {
+            let parents = keys.iter().map(|key| key.parent.clone()).collect();
+            let mut loaded = self.load_children::<R>(&keys[0].list, parents).await?;
+            for key in keys {
+                let models = loaded.remove(&key.parent).unwrap_or_default();
+                children.insert(key.clone(), models);
+            }
+        }
+        Ok(children)
+    }
+}
+
+impl QuasarDataLoader {
+    /// Loads a page of children for every parent in a single query, numbering
+    /// the children of each parent with `ROW_NUMBER()` and keeping the rows
+    /// numbered within the page
+    async fn load_children<R: ChildRelation>(
+        &self,
+        list: &ChildList<R::Entity>,
+        parents: Vec<R::Parent>,
+    ) -> Result<HashMap<R::Parent, Vec<<R::Entity as EntityTrait>::Model>>, Arc<DbErr>> {
+        let holder = R::Holder::default();
+        let parent = R::parent();
+        let window = format!(
+            "ROW_NUMBER() OVER (PARTITION BY \"{}\".\"{}\" ORDER BY {})",
+            holder.table_name(),
+            parent.as_str(),
+            order_by(&list.order)
+        );
+
+        let mut numbered = list
+            .query
+            .clone()
+            .filter(Expr::col((holder, parent)).is_in(parents.into_iter().map(Into::into)))
+            .into_query();
+        numbered
+            .expr_as(
+                Expr::col((R::Holder::default(), parent)),
+                Alias::new("parent_key"),
+            )
+            .expr_as(Expr::cust(window), Alias::new("row_number"));
+
+        let row_number = Alias::new("row_number");
+        let statement = self.pool.get_database_backend().build(
+            Query::select()
+                .column(Asterisk)
+                .from_subquery(numbered, Alias::new("numbered"))
+                .and_where(Expr::col(row_number.clone()).gt(list.offset))
+                .and_where(Expr::col(row_number.clone()).lte(list.offset + list.limit))
+                .order_by(row_number, Order::Asc),
+        );
+
+        let rows = self.pool.query_all(statement).await.map_err(Arc::new)?;
+
+        let mut grouped: HashMap<R::Parent, Vec<_>> = HashMap::new();
+        for row in rows {
+            let parent = row.try_get("", "parent_key").map_err(Arc::new)?;
+            let model =
+                <R::Entity as EntityTrait>::Model::from_query_result(&row, "").map_err(Arc::new)?;
+            grouped.entry(parent).or_default().push(model);
+        }
+        Ok(grouped)
+    }
+}
@@ -1,13 +1,20 @@
-use async_graphql::dataloader::Loader;
+use async_graphql::dataloader::{DataLoader, Loader};
 use async_graphql::{ComplexObject, Context};
 use sea_orm::{entity::prelude::*, ActiveValue::NotSet};
-use sea_orm::{Condition, Set};
+use sea_orm::{Condition, Order, Set};
 use std::collections::HashMap;
 use std::sync::Arc;
-use stellar_node_entities::contractdata;
-use stellar_xdr::curr::{Error, LedgerEntry, Limits, ReadXdr};
+use stellar_xdr::curr::{Error, LedgerEntry, LedgerEntryData, Limits, ScAddress, WriteXdr};
 
-use crate::{event, QuasarDataLoader};
+use crate::{
+    children::{ChildRelation, Children},
+    event::{self, ContractEvents},
+    filter::{EventFilter, OperationFilter},
+    operation::{self, ContractOperations},
+    pagination::Pagination,
+    sort::{sort_columns, EventSort, OperationSort},
+    QuasarDataLoader,
+};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, async_graphql::SimpleObject)]
 #[sea_orm(table_name = "contracts")]
@@ -28,10 +35,16 @@ pub struct Model {
 pub enum Relation {
     #[sea_orm(
         has_many = "super::event::Entity",
-        to = "super::event::Column::TransactionId",
+        to = "super::event::Column::ContractId",
         from = "Column::Address"
     )]
     Event,
+    #[sea_orm(
+        has_many = "super::operation::Entity",
+        to = "super::operation::Column::ContractId",
+        from = "Column::Address"
+    )]
+    Operation,
 }
 
 impl Related<super::event::Entity> for Entity {
@@ -40,38 +53,73 @@ impl Related<super::event::Entity> for Entity {
     }
 }
 
+impl Related<super::operation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Operation.def()
+    }
+}
+
 #[ComplexObject]
 impl Model {
-    pub async fn events<'ctx>(&self, ctx: &Context<'ctx>) -> Result<Vec<event::Model>, DbErr> {
-        let database = ctx
-            .data::<DatabaseConnection>()
-            .expect("DatabaseConnection missing from GraphQL context");
-        self.find_related(event::Entity).all(database).await
+    /// Events emitted by this contract, oldest first by default
+    pub async fn events<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        filter: Option<EventFilter>,
+        sort: Option<Vec<EventSort>>,
+        pagination: Option<Pagination>,
+    ) -> async_graphql::Result<Vec<event::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let query = ContractEvents::query();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+        let order = sort_columns(sort, &[(event::Column::Id, Order::Asc)]);
+        let key = Children::<ContractEvents>::new(self.address.clone(), query, order, pagination)?;
+        Ok(loader.load_one(key).await?.unwrap_or_default())
+    }
+
+    /// `InvokeHostFunction` operations calling this contract, oldest first by
+    /// default
+    pub async fn invocations<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        filter: Option<OperationFilter>,
+        sort: Option<Vec<OperationSort>>,
+        pagination: Option<Pagination>,
+    ) -> async_graphql::Result<Vec<operation::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let query = ContractOperations::query();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+        let order = sort_columns(sort, &[(operation::Column::Id, Order::Asc)]);
+        let key =
+            Children::<ContractOperations>::new(self.address.clone(), query, order, pagination)?;
+        Ok(loader.load_one(key).await?.unwrap_or_default())
     }
 }
 
 impl ActiveModelBehavior for ActiveModel {}
 
-impl TryFrom<contractdata::Model> for ActiveModel {
+impl TryFrom<LedgerEntry> for ActiveModel {
     type Error = Error;
 
-    fn try_from(model: contractdata::Model) -> Result<Self, Self::Error> {
-        let entry = LedgerEntry::from_xdr_base64(model.ledgerentry, Limits::none())?;
-        let address = match entry.data {
-            soroban_env_host::xdr::LedgerEntryData::ContractData(c) => match c.contract {
-                soroban_env_host::xdr::ScAddress::Contract(hash) => {
-                    Ok(stellar_strkey::Contract(hash.0).to_string())
-                }
-                _ => Err(Error::Invalid),
-            },
-            _ => Err(Error::Invalid),
-        }?;
+    fn try_from(entry: LedgerEntry) -> Result<Self, Self::Error> {
+        let contract_data = match entry.data {
+            LedgerEntryData::ContractData(contract_data) => contract_data,
+            _ => return Err(Error::Invalid),
+        };
+        let address = match &contract_data.contract {
+            ScAddress::Contract(hash) => stellar_strkey::Contract(hash.0).to_string(),
+            _ => return Err(Error::Invalid),
+        };
         Ok(Self {
-            r#type: Set(model.r#type.to_string()),
-            key: Set(model.key),
-            hash: Set(model.contractid.clone()),
+            r#type: Set((contract_data.durability as i32).to_string()),
+            key: Set(contract_data.key.to_xdr_base64(Limits::none())?),
+            hash: Set(contract_data.contract.to_xdr_base64(Limits::none())?),
             address: Set(address),
-            last_modified: Set(model.lastmodified),
+            last_modified: Set(entry.last_modified_ledger_seq as i32),
             created_at: NotSet,
         })
     }
@@ -1,14 +1,20 @@
-use async_graphql::{dataloader::Loader, ComplexObject, Context};
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context,
+};
 use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, Set};
 use serde_json::json;
 use std::collections::HashMap;
 use std::sync::Arc;
 use stellar_xdr::curr::{
-    ContractEvent, ContractEventBody, Error as StellarXdrError, Limits, ScVal, WriteXdr,
+    AccountId, ContractEvent, ContractEventBody, Error as StellarXdrError, Limits, PublicKey,
+    ReadXdr, ScAddress, ScVal, WriteXdr,
 };
 use thiserror::Error;
 
-use crate::{contract, transaction, QuasarDataLoader};
+use crate::{
+    children::ChildRelation, contract::ContractId, transaction::TransactionId, QuasarDataLoader,
+};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, async_graphql::SimpleObject)]
 #[sea_orm(table_name = "events")]
@@ -20,8 +26,15 @@ pub struct Model {
     pub topic: String,
     pub contract_id: String,
     pub transaction_id: String,
+    #[sea_orm(column_type = "JsonBinary")]
     pub value: Json,
     pub r#type: String,
+    /// Close time of the ledger
+    pub closed_at: Option<DateTimeWithTimeZone>,
+    /// Base64 encoded XDR of every topic
+    pub raw_topics: Option<Json>,
+    /// Base64 encoded XDR of the value
+    pub raw_value: Option<String>,
     pub created_at: DateTimeWithTimeZone,
 }
 
@@ -68,21 +81,25 @@ impl Model {
     pub async fn contract<'ctx>(
         &self,
         ctx: &Context<'ctx>,
-    ) -> Result<Option<super::contract::Model>, DbErr> {
-        let database = ctx
-            .data::<DatabaseConnection>()
-            .expect("DatabaseConnection missing from GraphQL context");
-        self.find_related(contract::Entity).one(database).await
+    ) -> async_graphql::Result<Option<super::contract::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        Ok(loader
+            .load_one(ContractId(self.contract_id.clone()))
+            .await?)
     }
 
     pub async fn transaction<'ctx>(
         &self,
         ctx: &Context<'ctx>,
-    ) -> Result<Option<super::transaction::Model>, DbErr> {
-        let database = ctx
-            .data::<DatabaseConnection>()
-            .expect("DatabaseConnection missing from GraphQL context");
-        self.find_related(transaction::Entity).one(database).await
+    ) -> async_graphql::Result<Option<super::transaction::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        Ok(loader
+            .load_one(TransactionId(self.transaction_id.clone()))
+            .await?)
     }
 }
 
@@ -92,7 +109,7 @@ impl TryFrom<ContractEvent> for ActiveModel {
     type Error = EventError;
 
     fn try_from(event: ContractEvent) -> Result<Self, Self::Error> {
-        let (topic, value) = match &event.body {
+        let (topic, value, raw_topics, raw_value) = match &event.body {
             ContractEventBody::V0(body) => {
                 let topic = &body.topics[0];
                 let topic = match topic {
@@ -100,7 +117,13 @@ impl TryFrom<ContractEvent> for ActiveModel {
                     _ => Err(EventError::Invalid)?,
                 };
                 let value = val_to_json(&body.data).map_err(|_| EventError::Invalid)?;
-                (topic, value)
+                let raw_topics = body
+                    .topics
+                    .iter()
+                    .map(|topic| topic.to_xdr_base64(Limits::none()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let raw_value = body.data.to_xdr_base64(Limits::none())?;
+                (topic, value, json!(raw_topics), raw_value)
             }
         };
         Ok(Self {
@@ -113,11 +136,22 @@ impl TryFrom<ContractEvent> for ActiveModel {
             transaction_id: NotSet,
             value: Set(value),
             r#type: Set(event.type_.to_string()),
+            closed_at: NotSet,
+            raw_topics: Set(Some(raw_topics)),
+            raw_value: Set(Some(raw_value)),
             created_at: NotSet,
         })
     }
 }
 
+/// Decodes the base64 encoded XDR of an event value to its JSON form
+pub fn decode_value(raw_value: &str) -> Result<Json, EventError> {
+    val_to_json(&ScVal::from_xdr_base64(raw_value, Limits::none())?)
+}
+
+/// Converts a contract value to JSON, 128 bits integers are numbers when they
+/// fit in 64 bits and decimal strings otherwise, maps with symbol or string
+/// keys are objects
 fn val_to_json(val: &ScVal) -> Result<Json, EventError> {
     let res = match val {
         ScVal::Bool(val) => json!(val),
@@ -131,14 +165,20 @@ fn val_to_json(val: &ScVal) -> Result<Json, EventError> {
         ScVal::I64(val) => json!(val),
         ScVal::Timepoint(t) => json!(t.0),
         ScVal::Duration(d) => json!(d.0),
-        ScVal::U128(val) => json!({
-            "hi": val.hi,
-            "low": val.lo
-        }),
-        ScVal::I128(val) => json!({
-            "hi": val.hi,
-            "low": val.lo
-        }),
+        ScVal::U128(val) => {
+            let val = (u128::from(val.hi) << 64) | u128::from(val.lo);
+            match u64::try_from(val) {
+                Ok(val) => json!(val),
+                Err(_) => json!(val.to_string()),
+            }
+        }
+        ScVal::I128(val) => {
+            let val = (i128::from(val.hi) << 64) | i128::from(val.lo);
+            match i64::try_from(val) {
+                Ok(val) => json!(val),
+                Err(_) => json!(val.to_string()),
+            }
+        }
         ScVal::U256(val) => json!({
             "hi_hi": val.hi_hi,
             "hi_lo": val.hi_lo,
@@ -163,20 +203,39 @@ fn val_to_json(val: &ScVal) -> Result<Json, EventError> {
             .iter()
             .map(val_to_json)
             .collect::<Result<Json, EventError>>()?,
-        ScVal::Map(Some(map)) => map
-            .iter()
-            .map(|v| {
-                let key = val_to_json(&v.key)?.to_string();
-                let val = val_to_json(&v.val)?;
-                Ok(json!({
-                    key: val
-                }))
-            })
-            .collect::<Result<Json, EventError>>()?,
+        ScVal::Map(Some(map)) => {
+            let named = map
+                .iter()
+                .all(|entry| matches!(entry.key, ScVal::Symbol(_) | ScVal::String(_)));
 
-        ScVal::Address(a) => json!({
-            "address": println!("{a:?}")
-        }),
+            if named {
+                let mut object = serde_json::Map::new();
+                for entry in map.iter() {
+                    let key = match &entry.key {
+                        ScVal::Symbol(key) => key.to_string(),
+                        ScVal::String(key) => key.to_string(),
+                        _ => unreachable!("keys are symbols or strings"),
+                    };
+                    object.insert(key, val_to_json(&entry.val)?);
+                }
+                Json::Object(object)
+            } else {
+                map.iter()
+                    .map(|entry| {
+                        Ok(json!({
+                            "key": val_to_json(&entry.key)?,
+                            "value": val_to_json(&entry.val)?,
+                        }))
+                    })
+                    .collect::<Result<Json, EventError>>()?
+            }
+        }
+        ScVal::Address(ScAddress::Account(AccountId(PublicKey::PublicKeyTypeEd25519(key)))) => {
+            json!(stellar_strkey::ed25519::PublicKey(key.0).to_string())
+        }
+        ScVal::Address(ScAddress::Contract(contract)) => {
+            json!(stellar_strkey::Contract(contract.0).to_string())
+        }
         _ => Json::Null,
     };
     Ok(res)
@@ -207,3 +266,105 @@ impl Loader<EventId> for QuasarDataLoader {
             .collect())
     }
 }
+
+/// Events emitted by a transaction, keyed by transaction id
+pub struct TransactionEvents;
+
+impl ChildRelation for TransactionEvents {
+    type Entity = Entity;
+    type Holder = Entity;
+    type Parent = String;
+
+    fn query() -> Select<Entity> {
+        Entity::find()
+    }
+
+    fn parent() -> Column {
+        Column::TransactionId
+    }
+}
+
+/// Events emitted by a contract, keyed by contract address
+pub struct ContractEvents;
+
+impl ChildRelation for ContractEvents {
+    type Entity = Entity;
+    type Holder = Entity;
+    type Parent = String;
+
+    fn query() -> Select<Entity> {
+        Entity::find()
+    }
+
+    fn parent() -> Column {
+        Column::ContractId
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stellar_xdr::curr::{
+        Hash, Int128Parts, ScMap, ScMapEntry, ScSymbol, UInt128Parts, Uint256,
+    };
+
+    use super::*;
+
+    fn symbol(name: &str) -> ScVal {
+        ScVal::Symbol(ScSymbol(name.try_into().unwrap()))
+    }
+
+    #[test]
+    fn addresses_are_strkeys() {
+        let account = ScVal::Address(ScAddress::Account(AccountId(
+            PublicKey::PublicKeyTypeEd25519(Uint256([0; 32])),
+        )));
+        let contract = ScVal::Address(ScAddress::Contract(Hash([0; 32])));
+
+        assert_eq!(
+            val_to_json(&account).unwrap(),
+            json!("GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF")
+        );
+        assert_eq!(
+            val_to_json(&contract).unwrap(),
+            json!("CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABSC4")
+        );
+    }
+
+    #[test]
+    fn integers_of_128_bits_are_decimal() {
+        let negative = ScVal::I128(Int128Parts {
+            hi: -1,
+            lo: u64::MAX - 4,
+        });
+        let large = ScVal::U128(UInt128Parts { hi: 1, lo: 0 });
+
+        assert_eq!(val_to_json(&negative).unwrap(), json!(-5));
+        assert_eq!(val_to_json(&large).unwrap(), json!("18446744073709551616"));
+    }
+
+    #[test]
+    fn maps_with_symbol_keys_are_objects() {
+        let named = ScVal::Map(Some(ScMap(
+            vec![ScMapEntry {
+                key: symbol("amount"),
+                val: ScVal::U32(7),
+            }]
+            .try_into()
+            .unwrap(),
+        )));
+        let numbered = ScVal::Map(Some(ScMap(
+            vec![ScMapEntry {
+                key: ScVal::U32(1),
+                val: symbol("one"),
+            }]
+            .try_into()
+            .unwrap(),
+        )));
+
+        assert_eq!(val_to_json(&named).unwrap(), json!({ "amount": 7 }));
+        assert_eq!(
+            val_to_json(&numbered).unwrap(),
+            json!([{ "key": 1, "value": { "symbol": "one" } }])
+        );
+    }
+}
@@ -0,0 +1,697 @@
+use crate::{account, contract, event, ledger, operation, prelude::*, transaction, upgrade};
+use async_graphql::{Enum, Error, InputObject, Json, Result};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone,
+    sea_query::{Expr, Func, LikeExpr, SimpleExpr},
+    ColumnTrait, Condition, EntityTrait, IntoSimpleExpr, QueryFilter, QuerySelect, QueryTrait,
+    Select, Value,
+};
+use serde_json::Value as JsonValue;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum Operator {
+    GreaterThan,
+    GreaterThanOrEqual,
+    LessThan,
+    LessThanOrEqual,
+    Equal,
+    /// Matches any of `values`
+    In,
+    /// Matches none of `values`
+    NotIn,
+    /// Matches the inclusive range between the two `values`
+    Between,
+}
+
+/// Builds the expression comparing a column with the operands of a filter,
+/// comparison operators use `value` while list and range operators use `values`
+fn compare<C, V>(
+    column: C,
+    op: Operator,
+    value: Option<&V>,
+    values: Option<&Vec<V>>,
+) -> Result<SimpleExpr>
+where
+    C: ColumnTrait,
+    V: Into<Value> + Clone,
+{
+    let value = || {
+        value
+            .cloned()
+            .ok_or_else(|| Error::new("`value` is required by comparison operators"))
+    };
+    let values = || {
+        values
+            .cloned()
+            .ok_or_else(|| Error::new("`values` is required by IN, NOT_IN and BETWEEN"))
+    };
+
+    let expression = match op {
+        Operator::GreaterThan => column.gt(value()?),
+        Operator::GreaterThanOrEqual => column.gte(value()?),
+        Operator::LessThan => column.lt(value()?),
+        Operator::LessThanOrEqual => column.lte(value()?),
+        Operator::Equal => column.eq(value()?),
+        Operator::In => column.is_in(values()?),
+        Operator::NotIn => column.is_not_in(values()?),
+        Operator::Between => match values()?.as_slice() {
+            [low, high] => column.between(low.clone(), high.clone()),
+            _ => return Err(Error::new("BETWEEN requires exactly two `values`")),
+        },
+    };
+
+    Ok(expression)
+}
+
+/// Adds the `and`, `or` and `not` combinators of a filter to the condition
+/// built from its fields
+fn combine<F>(
+    condition: Condition,
+    and: &Option<Vec<F>>,
+    or: &Option<Vec<F>>,
+    not: &Option<Box<F>>,
+    to_condition: fn(&F) -> Result<Condition>,
+) -> Result<Condition> {
+    let mut condition = condition;
+
+    if let Some(and) = and {
+        for filter in and {
+            condition = condition.add(to_condition(filter)?);
+        }
+    }
+
+    if let Some(or) = or {
+        let mut any = Condition::any();
+        for filter in or {
+            any = any.add(to_condition(filter)?);
+        }
+        condition = condition.add(any);
+    }
+
+    if let Some(not) = not {
+        condition = condition.add(to_condition(not)?.not());
+    }
+
+    Ok(condition)
+}
+
+#[derive(InputObject)]
+pub struct I32Filter {
+    pub op: Operator,
+    pub value: Option<i32>,
+    pub values: Option<Vec<i32>>,
+}
+
+impl I32Filter {
+    fn condition<C: ColumnTrait>(&self, column: C) -> Result<SimpleExpr> {
+        compare(column, self.op, self.value.as_ref(), self.values.as_ref())
+    }
+}
+
+#[derive(InputObject)]
+pub struct I64Filter {
+    pub op: Operator,
+    pub value: Option<i64>,
+    pub values: Option<Vec<i64>>,
+}
+
+impl I64Filter {
+    fn condition<C: ColumnTrait>(&self, column: C) -> Result<SimpleExpr> {
+        compare(column, self.op, self.value.as_ref(), self.values.as_ref())
+    }
+}
+
+#[derive(InputObject)]
+pub struct DateTimeFilter {
+    pub op: Operator,
+    pub value: Option<DateTimeWithTimeZone>,
+    pub values: Option<Vec<DateTimeWithTimeZone>>,
+}
+
+impl DateTimeFilter {
+    fn condition<C: ColumnTrait>(&self, column: C) -> Result<SimpleExpr> {
+        compare(column, self.op, self.value.as_ref(), self.values.as_ref())
+    }
+}
+
+/// Every operator that is set must match
+#[derive(InputObject)]
+pub struct StringFilter {
+    pub eq: Option<String>,
+    pub neq: Option<String>,
+    pub r#in: Option<Vec<String>>,
+    pub not_in: Option<Vec<String>>,
+    pub starts_with: Option<String>,
+    pub contains: Option<String>,
+    /// Compare values ignoring their case
+    #[graphql(default)]
+    pub case_insensitive: bool,
+}
+
+/// Escapes the wildcards of a `LIKE` pattern
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+impl StringFilter {
+    fn condition<C: ColumnTrait>(&self, column: C) -> Condition {
+        let mut condition = Condition::all();
+
+        let subject = || {
+            if self.case_insensitive {
+                Expr::expr(Func::lower(column.into_simple_expr()))
+            } else {
+                Expr::expr(column.into_simple_expr())
+            }
+        };
+        let normalize = |value: &String| {
+            if self.case_insensitive {
+                value.to_lowercase()
+            } else {
+                value.clone()
+            }
+        };
+
+        if let Some(eq) = &self.eq {
+            condition = condition.add(subject().eq(normalize(eq)));
+        }
+
+        if let Some(neq) = &self.neq {
+            condition = condition.add(subject().ne(normalize(neq)));
+        }
+
+        if let Some(values) = &self.r#in {
+            condition = condition.add(subject().is_in(values.iter().map(normalize)));
+        }
+
+        if let Some(values) = &self.not_in {
+            condition = condition.add(subject().is_not_in(values.iter().map(normalize)));
+        }
+
+        if let Some(prefix) = &self.starts_with {
+            let pattern = format!("{}%", escape_like(&normalize(prefix)));
+            condition = condition.add(subject().like(LikeExpr::new(pattern).escape('\\')));
+        }
+
+        if let Some(infix) = &self.contains {
+            let pattern = format!("%{}%", escape_like(&normalize(infix)));
+            condition = condition.add(subject().like(LikeExpr::new(pattern).escape('\\')));
+        }
+
+        condition
+    }
+}
+
+/// Compares the value found at `path` inside a JSON column
+///
+/// 128 bits integers that do not fit in 64 bits are stored as decimal strings,
+/// numeric comparisons skip them and they only match string values
+#[derive(InputObject)]
+pub struct JsonValueFilter {
+    /// Object keys, or array indexes, leading to the compared value. Numeric
+    /// segments are array indexes, a leading backslash makes a segment an
+    /// object key: `\0` for the key `0`, `\\x` for the key `\x`, written
+    /// `"\\0"` in a GraphQL string
+    pub path: Vec<String>,
+    pub op: Operator,
+    pub value: Option<Json<JsonValue>>,
+    pub values: Option<Vec<Json<JsonValue>>>,
+}
+
+/// Writes a scalar as a SQL/JSON path literal
+fn json_path_literal(value: &JsonValue) -> Result<String> {
+    match value {
+        JsonValue::Array(_) | JsonValue::Object(_) => {
+            Err(Error::new("only scalar JSON values can be compared"))
+        }
+        value => Ok(value.to_string()),
+    }
+}
+
+impl JsonValueFilter {
+    fn json_path(&self) -> String {
+        let mut json_path = "$".to_string();
+
+        for segment in &self.path {
+            if let Some(key) = segment.strip_prefix('\\') {
+                json_path.push('.');
+                json_path.push_str(&JsonValue::from(key).to_string());
+            } else if let Ok(index) = segment.parse::<usize>() {
+                json_path.push('[');
+                json_path.push_str(&index.to_string());
+                json_path.push(']');
+            } else {
+                json_path.push('.');
+                json_path.push_str(&JsonValue::from(segment.as_str()).to_string());
+            }
+        }
+
+        json_path
+    }
+
+    /// Builds a SQL/JSON path predicate evaluated with `@?`, which the GIN index
+    /// on the column supports and which ignores values of another type
+    fn condition<C: ColumnTrait>(&self, column: C) -> Result<SimpleExpr> {
+        let value = || match &self.value {
+            Some(value) => json_path_literal(value),
+            None => Err(Error::new("`value` is required by comparison operators")),
+        };
+        let values = || match &self.values {
+            Some(values) if !values.is_empty() => values
+                .iter()
+                .map(|value| json_path_literal(value))
+                .collect::<Result<Vec<_>>>(),
+            _ => Err(Error::new("`values` is required by IN, NOT_IN and BETWEEN")),
+        };
+        let any_of = |values: Vec<String>| {
+            values
+                .iter()
+                .map(|value| format!("@ == {}", value))
+                .collect::<Vec<_>>()
+                .join(" || ")
+        };
+
+        let predicate = match self.op {
+            Operator::GreaterThan => format!("@ > {}", value()?),
+            Operator::GreaterThanOrEqual => format!("@ >= {}", value()?),
+            Operator::LessThan => format!("@ < {}", value()?),
+            Operator::LessThanOrEqual => format!("@ <= {}", value()?),
+            Operator::Equal => format!("@ == {}", value()?),
+            Operator::In | Operator::NotIn => any_of(values()?),
+            Operator::Between => match values()?.as_slice() {
+                [low, high] => format!("@ >= {} && @ <= {}", low, high),
+                _ => return Err(Error::new("BETWEEN requires exactly two `values`")),
+            },
+        };
+
+        let json_path = format!("{} ? ({})", self.json_path(), predicate);
+        let expression = Expr::cust_with_exprs(
+            "$1 @? $2::jsonpath",
+            [column.into_simple_expr(), Expr::val(json_path).into()],
+        );
+
+        Ok(match self.op {
+            Operator::NotIn => expression.not(),
+            _ => expression,
+        })
+    }
+}
+
+#[derive(InputObject)]
+pub struct LedgerFilter {
+    pub hash: Option<StringFilter>,
+    pub sequence: Option<I32Filter>,
+    pub closed_at: Option<DateTimeFilter>,
+    pub transaction_count: Option<I32Filter>,
+    pub operation_count: Option<I32Filter>,
+    /// Every filter must match
+    pub and: Option<Vec<Self>>,
+    /// At least one filter must match
+    pub or: Option<Vec<Self>>,
+    /// The filter must not match
+    pub not: Option<Box<Self>>,
+}
+
+impl LedgerFilter {
+    pub fn apply(&self, query: Select<Ledger>) -> Result<Select<Ledger>> {
+        Ok(query.filter(self.condition()?))
+    }
+
+    fn condition(&self) -> Result<Condition> {
+        let mut condition = Condition::all();
+
+        if let Some(hash) = &self.hash {
+            condition = condition.add(hash.condition(ledger::Column::Hash));
+        }
+
+        if let Some(sequence) = &self.sequence {
+            condition = condition.add(sequence.condition(ledger::Column::Sequence)?);
+        }
+
+        if let Some(closed_at) = &self.closed_at {
+            condition = condition.add(closed_at.condition(ledger::Column::ClosedAt)?);
+        }
+
+        if let Some(transaction_count) = &self.transaction_count {
+            condition =
+                condition.add(transaction_count.condition(ledger::Column::TransactionCount)?);
+        }
+
+        if let Some(operation_count) = &self.operation_count {
+            condition = condition.add(operation_count.condition(ledger::Column::OperationCount)?);
+        }
+
+        combine(condition, &self.and, &self.or, &self.not, Self::condition)
+    }
+}
+
+#[derive(InputObject)]
+pub struct AccountFilter {
+    pub id: Option<StringFilter>,
+    pub home_domain: Option<StringFilter>,
+    pub inflation_destination: Option<StringFilter>,
+    pub balance: Option<I64Filter>,
+    pub buying_liabilities: Option<I64Filter>,
+    pub selling_liabilities: Option<I64Filter>,
+    pub sequence_number: Option<I64Filter>,
+    pub deleted: Option<bool>,
+    /// Every filter must match
+    pub and: Option<Vec<Self>>,
+    /// At least one filter must match
+    pub or: Option<Vec<Self>>,
+    /// The filter must not match
+    pub not: Option<Box<Self>>,
+}
+
+impl AccountFilter {
+    pub fn apply(&self, query: Select<Account>) -> Result<Select<Account>> {
+        Ok(query.filter(self.condition()?))
+    }
+
+    fn condition(&self) -> Result<Condition> {
+        let mut condition = Condition::all();
+
+        if let Some(id) = &self.id {
+            condition = condition.add(id.condition(account::Column::Id));
+        }
+
+        if let Some(home_domain) = &self.home_domain {
+            condition = condition.add(home_domain.condition(account::Column::HomeDomain));
+        }
+
+        if let Some(inflation_destination) = &self.inflation_destination {
+            condition = condition
+                .add(inflation_destination.condition(account::Column::InflationDestination));
+        }
+
+        if let Some(balance) = &self.balance {
+            condition = condition.add(balance.condition(account::Column::Balance)?);
+        }
+
+        if let Some(buying_liabilities) = &self.buying_liabilities {
+            condition =
+                condition.add(buying_liabilities.condition(account::Column::BuyingLiabilities)?);
+        }
+
+        if let Some(selling_liabilities) = &self.selling_liabilities {
+            condition =
+                condition.add(selling_liabilities.condition(account::Column::SellingLiabilities)?);
+        }
+
+        if let Some(sequence_number) = &self.sequence_number {
+            condition = condition.add(sequence_number.condition(account::Column::SequenceNumber)?);
+        }
+
+        if let Some(deleted) = self.deleted {
+            condition = if deleted {
+                condition.add(account::Column::DeletedInLedger.is_not_null())
+            } else {
+                condition.add(account::Column::DeletedInLedger.is_null())
+            };
+        }
+
+        combine(condition, &self.and, &self.or, &self.not, Self::condition)
+    }
+}
+
+#[derive(InputObject)]
+pub struct ContractFilter {
+    pub address: Option<StringFilter>,
+    pub r#type: Option<StringFilter>,
+    pub last_modified: Option<I32Filter>,
+    /// Every filter must match
+    pub and: Option<Vec<Self>>,
+    /// At least one filter must match
+    pub or: Option<Vec<Self>>,
+    /// The filter must not match
+    pub not: Option<Box<Self>>,
+}
+
+impl ContractFilter {
+    pub fn apply(&self, query: Select<Contract>) -> Result<Select<Contract>> {
+        Ok(query.filter(self.condition()?))
+    }
+
+    fn condition(&self) -> Result<Condition> {
+        let mut condition = Condition::all();
+
+        if let Some(address) = &self.address {
+            condition = condition.add(address.condition(contract::Column::Address));
+        }
+
+        if let Some(r#type) = &self.r#type {
+            condition = condition.add(r#type.condition(contract::Column::Type));
+        }
+
+        if let Some(last_modified) = &self.last_modified {
+            condition = condition.add(last_modified.condition(contract::Column::LastModified)?);
+        }
+
+        combine(condition, &self.and, &self.or, &self.not, Self::condition)
+    }
+}
+
+#[derive(InputObject)]
+pub struct EventFilter {
+    pub contract_id: Option<StringFilter>,
+    pub topic: Option<StringFilter>,
+    pub r#type: Option<StringFilter>,
+    /// Sequence of the ledger the event was emitted in
+    pub ledger_sequence: Option<I32Filter>,
+    pub closed_at: Option<DateTimeFilter>,
+    /// Conditions on the decoded event value, every condition must match
+    pub value: Option<Vec<JsonValueFilter>>,
+    /// Every filter must match
+    pub and: Option<Vec<Self>>,
+    /// At least one filter must match
+    pub or: Option<Vec<Self>>,
+    /// The filter must not match
+    pub not: Option<Box<Self>>,
+}
+
+impl EventFilter {
+    pub fn apply(&self, query: Select<Event>) -> Result<Select<Event>> {
+        Ok(query.filter(self.condition()?))
+    }
+
+    fn condition(&self) -> Result<Condition> {
+        let mut condition = Condition::all();
+
+        if let Some(contract_id) = &self.contract_id {
+            condition = condition.add(contract_id.condition(event::Column::ContractId));
+        }
+
+        if let Some(topic) = &self.topic {
+            condition = condition.add(topic.condition(event::Column::Topic));
+        }
+
+        if let Some(r#type) = &self.r#type {
+            condition = condition.add(r#type.condition(event::Column::Type));
+        }
+
+        if let Some(ledger_sequence) = &self.ledger_sequence {
+            let transactions = Transaction::find()
+                .select_only()
+                .column(transaction::Column::Id)
+                .filter(ledger_sequence.condition(transaction::Column::LedgerSequence)?)
+                .into_query();
+
+            condition = condition.add(event::Column::TransactionId.in_subquery(transactions));
+        }
+
+        if let Some(closed_at) = &self.closed_at {
+            condition = condition.add(closed_at.condition(event::Column::ClosedAt)?);
+        }
+
+        if let Some(value) = &self.value {
+            for value in value {
+                condition = condition.add(value.condition(event::Column::Value)?);
+            }
+        }
+
+        combine(condition, &self.and, &self.or, &self.not, Self::condition)
+    }
+}
+
+#[derive(InputObject)]
+pub struct OperationFilter {
+    pub r#type: Option<StringFilter>,
+    pub application_order: Option<I32Filter>,
+    pub closed_at: Option<DateTimeFilter>,
+    /// Every filter must match
+    pub and: Option<Vec<Self>>,
+    /// At least one filter must match
+    pub or: Option<Vec<Self>>,
+    /// The filter must not match
+    pub not: Option<Box<Self>>,
+}
+
+impl OperationFilter {
+    pub fn apply(&self, query: Select<Operation>) -> Result<Select<Operation>> {
+        Ok(query.filter(self.condition()?))
+    }
+
+    fn condition(&self) -> Result<Condition> {
+        let mut condition = Condition::all();
+
+        if let Some(r#type) = &self.r#type {
+            condition = condition.add(r#type.condition(operation::Column::Type));
+        }
+
+        if let Some(application_order) = &self.application_order {
+            condition =
+                condition.add(application_order.condition(operation::Column::ApplicationOrder)?);
+        }
+
+        if let Some(closed_at) = &self.closed_at {
+            condition = condition.add(closed_at.condition(operation::Column::ClosedAt)?);
+        }
+
+        combine(condition, &self.and, &self.or, &self.not, Self::condition)
+    }
+}
+
+#[derive(InputObject)]
+pub struct TransactionFilter {
+    /// Hash of the transaction
+    pub id: Option<StringFilter>,
+    /// Source account of the transaction
+    pub account_id: Option<StringFilter>,
+    pub ledger_sequence: Option<I32Filter>,
+    pub application_order: Option<I32Filter>,
+    pub account_sequence: Option<I64Filter>,
+    pub operation_count: Option<I32Filter>,
+    pub closed_at: Option<DateTimeFilter>,
+    /// Every filter must match
+    pub and: Option<Vec<Self>>,
+    /// At least one filter must match
+    pub or: Option<Vec<Self>>,
+    /// The filter must not match
+    pub not: Option<Box<Self>>,
+}
+
+impl TransactionFilter {
+    pub fn apply(&self, query: Select<Transaction>) -> Result<Select<Transaction>> {
+        Ok(query.filter(self.condition()?))
+    }
+
+    fn condition(&self) -> Result<Condition> {
+        let mut condition = Condition::all();
+
+        if let Some(id) = &self.id {
+            condition = condition.add(id.condition(transaction::Column::Id));
+        }
+
+        if let Some(account_id) = &self.account_id {
+            condition = condition.add(account_id.condition(transaction::Column::AccountId));
+        }
+
+        if let Some(ledger_sequence) = &self.ledger_sequence {
+            condition =
+                condition.add(ledger_sequence.condition(transaction::Column::LedgerSequence)?);
+        }
+
+        if let Some(application_order) = &self.application_order {
+            condition =
+                condition.add(application_order.condition(transaction::Column::ApplicationOrder)?);
+        }
+
+        if let Some(account_sequence) = &self.account_sequence {
+            condition =
+                condition.add(account_sequence.condition(transaction::Column::AccountSequence)?);
+        }
+
+        if let Some(operation_count) = &self.operation_count {
+            condition =
+                condition.add(operation_count.condition(transaction::Column::OperationCount)?);
+        }
+
+        if let Some(closed_at) = &self.closed_at {
+            condition = condition.add(closed_at.condition(transaction::Column::ClosedAt)?);
+        }
+
+        combine(condition, &self.and, &self.or, &self.not, Self::condition)
+    }
+}
+
+#[derive(InputObject)]
+pub struct UpgradeFilter {
+    pub r#type: Option<StringFilter>,
+    pub ledger_sequence: Option<I32Filter>,
+    /// Every filter must match
+    pub and: Option<Vec<Self>>,
+    /// At least one filter must match
+    pub or: Option<Vec<Self>>,
+    /// The filter must not match
+    pub not: Option<Box<Self>>,
+}
+
+impl UpgradeFilter {
+    pub fn apply(&self, query: Select<Upgrade>) -> Result<Select<Upgrade>> {
+        Ok(query.filter(self.condition()?))
+    }
+
+    fn condition(&self) -> Result<Condition> {
+        let mut condition = Condition::all();
+
+        if let Some(r#type) = &self.r#type {
+            condition = condition.add(r#type.condition(upgrade::Column::Type));
+        }
+
+        if let Some(ledger_sequence) = &self.ledger_sequence {
+            condition = condition.add(ledger_sequence.condition(upgrade::Column::LedgerSequence)?);
+        }
+
+        combine(condition, &self.and, &self.or, &self.not, Self::condition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json_value_filter(path: &[&str]) -> JsonValueFilter {
+        JsonValueFilter {
+            path: path.iter().map(|segment| segment.to_string()).collect(),
+            op: Operator::Equal,
+            value: None,
+            values: None,
+        }
+    }
+
+    #[test]
+    fn json_path_quotes_keys_and_indexes_arrays() {
+        assert_eq!(json_value_filter(&[]).json_path(), "$");
+        assert_eq!(
+            json_value_filter(&["amount", "0", "hi"]).json_path(),
+            r#"$."amount"[0]."hi""#
+        );
+    }
+
+    #[test]
+    fn json_path_reads_escaped_segments_as_keys() {
+        assert_eq!(
+            json_value_filter(&["balances", r"\0", r"\\x"]).json_path(),
+            r#"$."balances"."0"."\\x""#
+        );
+    }
+
+    #[test]
+    fn json_path_escapes_quotes_in_keys() {
+        assert_eq!(
+            json_value_filter(&[r#"a"b"#, r"c\d"]).json_path(),
+            r#"$."a\"b"."c\\d""#
+        );
+    }
+
+    #[test]
+    fn escape_like_escapes_wildcards() {
+        assert_eq!(escape_like("plain"), "plain");
+        assert_eq!(escape_like("100%"), r"100\%");
+        assert_eq!(escape_like("a_b"), r"a\_b");
+        assert_eq!(escape_like(r"a\%_"), r"a\\\%\_");
+    }
+}
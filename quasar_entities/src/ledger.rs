@@ -1,6 +1,18 @@
-use crate::{account, QuasarDataLoader};
-use async_graphql::{dataloader::Loader, ComplexObject, Context};
-use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, Set};
+use crate::{
+    account::{self, LedgerAccounts},
+    children::{ChildRelation, Children},
+    filter::TransactionFilter,
+    pagination::Pagination,
+    sort::{sort_columns, TransactionSort},
+    transaction::{self, LedgerTransactions},
+    upgrade::{self, LedgerUpgrades},
+    QuasarDataLoader,
+};
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context,
+};
+use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, Order, Set};
 use std::{collections::HashMap, sync::Arc};
 use stellar_node_entities::ledgerheaders;
 use stellar_xdr::curr::{Error, LedgerHeader, Limits, ReadXdr};
@@ -22,6 +34,14 @@ pub struct Model {
     pub base_fee: i32,
     pub base_reserve: i32,
     pub max_tx_set_size: i32,
+    pub closed_at: Option<DateTimeWithTimeZone>,
+    pub tx_set_hash: Option<String>,
+    /// Unknown for ledgers ingested before it was stored whose transaction
+    /// history the node no longer holds
+    pub transaction_count: Option<i32>,
+    pub successful_transaction_count: Option<i32>,
+    pub failed_transaction_count: Option<i32>,
+    pub operation_count: Option<i32>,
     pub created_at: DateTimeWithTimeZone,
 }
 
@@ -33,6 +53,18 @@ pub enum Relation {
         from = "Column::Sequence"
     )]
     Account,
+    #[sea_orm(
+        has_many = "super::upgrade::Entity",
+        to = "super::upgrade::Column::LedgerSequence",
+        from = "Column::Sequence"
+    )]
+    Upgrade,
+    #[sea_orm(
+        has_many = "super::transaction::Entity",
+        to = "super::transaction::Column::LedgerSequence",
+        from = "Column::Sequence"
+    )]
+    Transaction,
 }
 
 impl Related<super::account::Entity> for Entity {
@@ -41,15 +73,61 @@ impl Related<super::account::Entity> for Entity {
     }
 }
 
+impl Related<super::upgrade::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Upgrade.def()
+    }
+}
+
+impl Related<super::transaction::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Transaction.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
 #[ComplexObject]
 impl Model {
-    pub async fn accounts<'ctx>(&self, ctx: &Context<'ctx>) -> Result<Vec<account::Model>, DbErr> {
-        let database = ctx
-            .data::<DatabaseConnection>()
-            .expect("DatabaseConnection missing from GraphQL context");
-        self.find_related(account::Entity).all(database).await
+    pub async fn accounts<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Vec<account::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let accounts = loader.load_one(LedgerAccounts(self.sequence)).await?;
+        Ok(accounts.unwrap_or_default())
+    }
+
+    /// Network upgrades applied at the end of this ledger
+    pub async fn upgrades<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Vec<upgrade::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let upgrades = loader.load_one(LedgerUpgrades(self.sequence)).await?;
+        Ok(upgrades.unwrap_or_default())
+    }
+
+    /// Transactions of this ledger, in application order by default
+    pub async fn transactions<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        filter: Option<TransactionFilter>,
+        sort: Option<Vec<TransactionSort>>,
+        pagination: Option<Pagination>,
+    ) -> async_graphql::Result<Vec<transaction::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let query = LedgerTransactions::query();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+        let order = sort_columns(sort, &[(transaction::Column::ApplicationOrder, Order::Asc)]);
+        let key = Children::<LedgerTransactions>::new(self.sequence, query, order, pagination)?;
+        Ok(loader.load_one(key).await?.unwrap_or_default())
     }
 }
 
@@ -71,6 +149,16 @@ impl TryFrom<ledgerheaders::Model> for ActiveModel {
             base_fee: Set(ledgerheader_data.base_fee as i32),
             base_reserve: Set(ledgerheader_data.base_reserve as i32),
             max_tx_set_size: Set(ledgerheader_data.max_tx_set_size as i32),
+            closed_at: Set(DateTimeUtc::from_timestamp(
+                ledgerheader_data.scp_value.close_time.0 as i64,
+                0,
+            )
+            .map(Into::into)),
+            tx_set_hash: Set(Some(ledgerheader_data.scp_value.tx_set_hash.to_string())),
+            transaction_count: NotSet,
+            successful_transaction_count: NotSet,
+            failed_transaction_count: NotSet,
+            operation_count: NotSet,
             created_at: NotSet,
         })
     }
@@ -104,3 +192,32 @@ impl Loader<LedgerHash> for QuasarDataLoader {
             .collect())
     }
 }
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LedgerSequence(pub i32);
+
+#[async_trait::async_trait]
+impl Loader<LedgerSequence> for QuasarDataLoader {
+    type Value = Model;
+    type Error = Arc<DbErr>;
+
+    async fn load(
+        &self,
+        keys: &[LedgerSequence],
+    ) -> Result<HashMap<LedgerSequence, Self::Value>, Self::Error> {
+        let mut condition = Condition::any();
+
+        for LedgerSequence(sequence) in keys {
+            condition = condition.add(Column::Sequence.eq(*sequence));
+        }
+        let ledgers = Entity::find()
+            .filter(condition)
+            .all(&self.pool)
+            .await
+            .map_err(Arc::new)?;
+        Ok(ledgers
+            .into_iter()
+            .map(|ledger| (LedgerSequence(ledger.sequence), ledger))
+            .collect())
+    }
+}
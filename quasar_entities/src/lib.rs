@@ -1,13 +1,30 @@
-use sea_orm::DatabaseConnection;
+use std::{collections::HashMap, hash::Hash, sync::Arc};
+
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, Select};
 
 pub mod prelude;
 
 pub mod account;
+pub mod account_data;
+pub mod account_history;
+pub mod account_signer;
+pub mod api_key;
+pub mod backfill;
+pub mod children;
 pub mod contract;
 pub mod event;
+pub mod filter;
 pub mod ledger;
+pub mod network_config;
 pub mod operation;
+pub mod pagination;
+pub mod quorum_node;
+pub mod quorum_set;
+pub mod sort;
 pub mod transaction;
+pub mod upgrade;
+pub mod webhook;
+pub mod webhook_dead_letter;
 
 #[derive(Clone, Debug)]
 pub struct QuasarDataLoader {
@@ -18,4 +35,25 @@ impl QuasarDataLoader {
     pub fn new(pool: DatabaseConnection) -> Self {
         Self { pool }
     }
+
+    /// Runs the query and groups the rows by the key of their parent, keeping
+    /// the order of the query within each group
+    pub(crate) async fn load_grouped<E, K, F>(
+        &self,
+        query: Select<E>,
+        key: F,
+    ) -> Result<HashMap<K, Vec<E::Model>>, Arc<DbErr>>
+    where
+        E: EntityTrait,
+        K: Eq + Hash,
+        F: Fn(&E::Model) -> K,
+    {
+        let models = query.all(&self.pool).await.map_err(Arc::new)?;
+
+        let mut grouped: HashMap<K, Vec<E::Model>> = HashMap::new();
+        for model in models {
+            grouped.entry(key(&model)).or_default().push(model);
+        }
+        Ok(grouped)
+    }
 }
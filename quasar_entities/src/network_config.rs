@@ -0,0 +1,143 @@
+use sea_orm::{entity::prelude::*, Set};
+use stellar_xdr::curr::ConfigSettingEntry;
+
+/// Soroban resource limits and fee rates, a row is written for every ledger in
+/// which one of the settings changed and holds the full configuration as of that ledger
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, async_graphql::SimpleObject)]
+#[sea_orm(table_name = "network_config")]
+#[graphql(name = "NetworkConfig")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub ledger_sequence: i32,
+    pub contract_max_size_bytes: Option<i32>,
+    pub ledger_max_instructions: Option<i64>,
+    pub tx_max_instructions: Option<i64>,
+    pub fee_rate_per_instructions_increment: Option<i64>,
+    pub tx_memory_limit: Option<i32>,
+    pub ledger_max_read_ledger_entries: Option<i32>,
+    pub ledger_max_read_bytes: Option<i32>,
+    pub ledger_max_write_ledger_entries: Option<i32>,
+    pub ledger_max_write_bytes: Option<i32>,
+    pub tx_max_read_ledger_entries: Option<i32>,
+    pub tx_max_read_bytes: Option<i32>,
+    pub tx_max_write_ledger_entries: Option<i32>,
+    pub tx_max_write_bytes: Option<i32>,
+    pub fee_read_ledger_entry: Option<i64>,
+    pub fee_write_ledger_entry: Option<i64>,
+    pub fee_read_1kb: Option<i64>,
+    pub bucket_list_target_size_bytes: Option<i64>,
+    pub write_fee_1kb_bucket_list_low: Option<i64>,
+    pub write_fee_1kb_bucket_list_high: Option<i64>,
+    pub bucket_list_write_fee_growth_factor: Option<i32>,
+    pub fee_historical_1kb: Option<i64>,
+    pub tx_max_contract_events_size_bytes: Option<i32>,
+    pub fee_contract_events_1kb: Option<i64>,
+    pub ledger_max_txs_size_bytes: Option<i32>,
+    pub tx_max_size_bytes: Option<i32>,
+    pub fee_tx_size_1kb: Option<i64>,
+    pub contract_data_key_size_bytes: Option<i32>,
+    pub contract_data_entry_size_bytes: Option<i32>,
+    pub max_entry_ttl: Option<i32>,
+    pub min_temporary_ttl: Option<i32>,
+    pub min_persistent_ttl: Option<i32>,
+    pub persistent_rent_rate_denominator: Option<i64>,
+    pub temp_rent_rate_denominator: Option<i64>,
+    pub max_entries_to_archive: Option<i32>,
+    pub bucket_list_size_window_sample_size: Option<i32>,
+    pub bucket_list_window_sample_period: Option<i32>,
+    pub eviction_scan_size: Option<i32>,
+    pub starting_eviction_scan_level: Option<i32>,
+    pub ledger_max_tx_count: Option<i32>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    /// Applies a config setting to the network config, returns whether the
+    /// setting is part of it, cost parameters and the bucket list and eviction
+    /// state updated by the network itself are not
+    pub fn apply_setting(&mut self, setting: &ConfigSettingEntry) -> bool {
+        match setting {
+            ConfigSettingEntry::ContractMaxSizeBytes(size) => {
+                self.contract_max_size_bytes = Set(Some(*size as i32));
+            }
+            ConfigSettingEntry::ContractComputeV0(compute) => {
+                self.ledger_max_instructions = Set(Some(compute.ledger_max_instructions));
+                self.tx_max_instructions = Set(Some(compute.tx_max_instructions));
+                self.fee_rate_per_instructions_increment =
+                    Set(Some(compute.fee_rate_per_instructions_increment));
+                self.tx_memory_limit = Set(Some(compute.tx_memory_limit as i32));
+            }
+            ConfigSettingEntry::ContractLedgerCostV0(cost) => {
+                self.ledger_max_read_ledger_entries =
+                    Set(Some(cost.ledger_max_read_ledger_entries as i32));
+                self.ledger_max_read_bytes = Set(Some(cost.ledger_max_read_bytes as i32));
+                self.ledger_max_write_ledger_entries =
+                    Set(Some(cost.ledger_max_write_ledger_entries as i32));
+                self.ledger_max_write_bytes = Set(Some(cost.ledger_max_write_bytes as i32));
+                self.tx_max_read_ledger_entries = Set(Some(cost.tx_max_read_ledger_entries as i32));
+                self.tx_max_read_bytes = Set(Some(cost.tx_max_read_bytes as i32));
+                self.tx_max_write_ledger_entries =
+                    Set(Some(cost.tx_max_write_ledger_entries as i32));
+                self.tx_max_write_bytes = Set(Some(cost.tx_max_write_bytes as i32));
+                self.fee_read_ledger_entry = Set(Some(cost.fee_read_ledger_entry));
+                self.fee_write_ledger_entry = Set(Some(cost.fee_write_ledger_entry));
+                self.fee_read_1kb = Set(Some(cost.fee_read1_kb));
+                self.bucket_list_target_size_bytes = Set(Some(cost.bucket_list_target_size_bytes));
+                self.write_fee_1kb_bucket_list_low = Set(Some(cost.write_fee1_kb_bucket_list_low));
+                self.write_fee_1kb_bucket_list_high =
+                    Set(Some(cost.write_fee1_kb_bucket_list_high));
+                self.bucket_list_write_fee_growth_factor =
+                    Set(Some(cost.bucket_list_write_fee_growth_factor as i32));
+            }
+            ConfigSettingEntry::ContractHistoricalDataV0(historical_data) => {
+                self.fee_historical_1kb = Set(Some(historical_data.fee_historical1_kb));
+            }
+            ConfigSettingEntry::ContractEventsV0(events) => {
+                self.tx_max_contract_events_size_bytes =
+                    Set(Some(events.tx_max_contract_events_size_bytes as i32));
+                self.fee_contract_events_1kb = Set(Some(events.fee_contract_events1_kb));
+            }
+            ConfigSettingEntry::ContractBandwidthV0(bandwidth) => {
+                self.ledger_max_txs_size_bytes =
+                    Set(Some(bandwidth.ledger_max_txs_size_bytes as i32));
+                self.tx_max_size_bytes = Set(Some(bandwidth.tx_max_size_bytes as i32));
+                self.fee_tx_size_1kb = Set(Some(bandwidth.fee_tx_size1_kb));
+            }
+            ConfigSettingEntry::ContractDataKeySizeBytes(size) => {
+                self.contract_data_key_size_bytes = Set(Some(*size as i32));
+            }
+            ConfigSettingEntry::ContractDataEntrySizeBytes(size) => {
+                self.contract_data_entry_size_bytes = Set(Some(*size as i32));
+            }
+            ConfigSettingEntry::StateArchival(archival) => {
+                self.max_entry_ttl = Set(Some(archival.max_entry_ttl as i32));
+                self.min_temporary_ttl = Set(Some(archival.min_temporary_ttl as i32));
+                self.min_persistent_ttl = Set(Some(archival.min_persistent_ttl as i32));
+                self.persistent_rent_rate_denominator =
+                    Set(Some(archival.persistent_rent_rate_denominator));
+                self.temp_rent_rate_denominator = Set(Some(archival.temp_rent_rate_denominator));
+                self.max_entries_to_archive = Set(Some(archival.max_entries_to_archive as i32));
+                self.bucket_list_size_window_sample_size =
+                    Set(Some(archival.bucket_list_size_window_sample_size as i32));
+                self.bucket_list_window_sample_period =
+                    Set(Some(archival.bucket_list_window_sample_period as i32));
+                self.eviction_scan_size = Set(Some(archival.eviction_scan_size as i32));
+                self.starting_eviction_scan_level =
+                    Set(Some(archival.starting_eviction_scan_level as i32));
+            }
+            ConfigSettingEntry::ContractExecutionLanes(lanes) => {
+                self.ledger_max_tx_count = Set(Some(lanes.ledger_max_tx_count as i32));
+            }
+            ConfigSettingEntry::ContractCostParamsCpuInstructions(_)
+            | ConfigSettingEntry::ContractCostParamsMemoryBytes(_)
+            | ConfigSettingEntry::BucketlistSizeWindow(_)
+            | ConfigSettingEntry::EvictionIterator(_) => return false,
+        }
+        true
+    }
+}
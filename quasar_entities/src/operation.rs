@@ -1,10 +1,19 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::{transaction, QuasarDataLoader};
-use async_graphql::{dataloader::Loader, ComplexObject, Context};
-use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, Set};
-use stellar_xdr::curr::{Error, Operation};
+use crate::{
+    account::{self, AccountId},
+    children::ChildRelation,
+    ledger::{self, LedgerSequence},
+    transaction::{self, TransactionId},
+    QuasarDataLoader,
+};
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context,
+};
+use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, JoinType, QuerySelect, Set};
+use stellar_xdr::curr::{Error, HostFunction, Operation, OperationBody, ScAddress};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, async_graphql::SimpleObject)]
 #[sea_orm(table_name = "operations")]
@@ -12,10 +21,14 @@ use stellar_xdr::curr::{Error, Operation};
 #[graphql(name = "Operations")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = true)]
-    id: i32,
-    transaction_id: String,
-    application_order: i32,
-    r#type: String,
+    pub id: i32,
+    pub transaction_id: String,
+    pub application_order: i32,
+    pub r#type: String,
+    /// Close time of the ledger
+    pub closed_at: Option<DateTimeWithTimeZone>,
+    /// Contract called by an `InvokeHostFunction` operation
+    pub contract_id: Option<String>,
     pub created_at: DateTimeWithTimeZone,
 }
 
@@ -27,6 +40,12 @@ pub enum Relation {
         to = "super::transaction::Column::Id"
     )]
     Transaction,
+    #[sea_orm(
+        belongs_to = "super::contract::Entity",
+        from = "Column::ContractId",
+        to = "super::contract::Column::Address"
+    )]
+    Contract,
 }
 
 impl Related<super::transaction::Entity> for Entity {
@@ -35,6 +54,32 @@ impl Related<super::transaction::Entity> for Entity {
     }
 }
 
+impl Related<super::contract::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Contract.def()
+    }
+}
+
+impl Related<super::ledger::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::transaction::Relation::Ledger.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(Relation::Transaction.def())
+    }
+}
+
+impl Related<super::account::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::transaction::Relation::Account.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(Relation::Transaction.def())
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
 #[ComplexObject]
@@ -42,11 +87,49 @@ impl Model {
     pub async fn transaction<'ctx>(
         &self,
         ctx: &Context<'ctx>,
-    ) -> Result<Option<super::transaction::Model>, DbErr> {
-        let database = ctx
-            .data::<DatabaseConnection>()
-            .expect("DatabaseConnection missing from GraphQL context");
-        self.find_related(transaction::Entity).one(database).await
+    ) -> async_graphql::Result<Option<super::transaction::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        Ok(loader
+            .load_one(TransactionId(self.transaction_id.clone()))
+            .await?)
+    }
+
+    /// Ledger the operation was applied in
+    pub async fn ledger<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Option<ledger::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let transaction: Option<transaction::Model> = loader
+            .load_one(TransactionId(self.transaction_id.clone()))
+            .await?;
+        match transaction {
+            Some(transaction) => Ok(loader
+                .load_one(LedgerSequence(transaction.ledger_sequence))
+                .await?),
+            None => Ok(None),
+        }
+    }
+
+    /// Source account of the transaction of the operation
+    pub async fn account<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Option<account::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let transaction: Option<transaction::Model> = loader
+            .load_one(TransactionId(self.transaction_id.clone()))
+            .await?;
+        match transaction {
+            Some(transaction) => Ok(loader.load_one(AccountId(transaction.account_id)).await?),
+            None => Ok(None),
+        }
     }
 }
 
@@ -59,11 +142,28 @@ impl TryFrom<Operation> for ActiveModel {
             transaction_id: NotSet,
             application_order: NotSet,
             r#type: Set(operation.body.name().to_string()),
+            closed_at: NotSet,
+            contract_id: Set(invoked_contract(&operation)),
             created_at: NotSet,
         })
     }
 }
 
+/// Address of the contract called by an `InvokeHostFunction` operation,
+/// uploading Wasm and creating contracts call none
+fn invoked_contract(operation: &Operation) -> Option<String> {
+    match &operation.body {
+        OperationBody::InvokeHostFunction(invoke) => match &invoke.host_function {
+            HostFunction::InvokeContract(args) => match &args.contract_address {
+                ScAddress::Contract(hash) => Some(stellar_strkey::Contract(hash.0).to_string()),
+                ScAddress::Account(_) => None,
+            },
+            HostFunction::CreateContract(_) | HostFunction::UploadContractWasm(_) => None,
+        },
+        _ => None,
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct OperationId(pub i32);
 
@@ -92,3 +192,54 @@ impl Loader<OperationId> for QuasarDataLoader {
             .collect())
     }
 }
+
+/// Operations of a transaction, keyed by transaction id
+pub struct TransactionOperations;
+
+impl ChildRelation for TransactionOperations {
+    type Entity = Entity;
+    type Holder = Entity;
+    type Parent = String;
+
+    fn query() -> Select<Entity> {
+        Entity::find()
+    }
+
+    fn parent() -> Column {
+        Column::TransactionId
+    }
+}
+
+/// Operations of the transactions submitted by an account, keyed by account id
+pub struct AccountOperations;
+
+impl ChildRelation for AccountOperations {
+    type Entity = Entity;
+    type Holder = transaction::Entity;
+    type Parent = String;
+
+    fn query() -> Select<Entity> {
+        Entity::find().join(JoinType::InnerJoin, Relation::Transaction.def())
+    }
+
+    fn parent() -> transaction::Column {
+        transaction::Column::AccountId
+    }
+}
+
+/// Invocations of a contract, keyed by contract address
+pub struct ContractOperations;
+
+impl ChildRelation for ContractOperations {
+    type Entity = Entity;
+    type Holder = Entity;
+    type Parent = String;
+
+    fn query() -> Select<Entity> {
+        Entity::find()
+    }
+
+    fn parent() -> Column {
+        Column::ContractId
+    }
+}
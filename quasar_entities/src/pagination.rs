@@ -0,0 +1,187 @@
+use std::{future::Future, pin::Pin};
+
+use async_graphql::{ComplexObject, Context, Error, InputObject, OutputType, SimpleObject};
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, PaginatorTrait,
+    QuerySelect, QueryTrait, Select, Statement,
+};
+
+use crate::{account, contract, event, ledger, operation, quorum_set, transaction, upgrade};
+
+/// Rows returned by list queries, and by nested lists for each parent, when no
+/// page is requested
+pub const DEFAULT_PER_PAGE: u64 = 100;
+/// Rows returned at most on a page, larger `perPage` values are clamped
+pub const MAX_PER_PAGE: u64 = 1000;
+
+#[derive(InputObject)]
+pub struct Pagination {
+    /// Page number, starting from 1
+    pub page: u64,
+    pub per_page: u64,
+}
+
+impl Pagination {
+    /// Offset and limit of the page
+    pub fn bounds(&self) -> Result<(u64, u64), Error> {
+        if self.page == 0 {
+            return Err(Error::new("Pages are numbered from 1"));
+        }
+
+        let per_page = self.per_page.min(MAX_PER_PAGE);
+        let offset = (self.page - 1)
+            .checked_mul(per_page)
+            .ok_or_else(|| Error::new("Page out of range"))?;
+
+        Ok((offset, per_page))
+    }
+}
+
+pub fn apply_pagination<E: EntityTrait>(
+    query: Select<E>,
+    pagination: Option<Pagination>,
+) -> Result<Select<E>, Error> {
+    match pagination {
+        Some(pagination) => {
+            let (offset, per_page) = pagination.bounds()?;
+            Ok(query.offset(offset).limit(per_page))
+        }
+        None => Ok(query),
+    }
+}
+
+type CountFuture = Pin<Box<dyn Future<Output = Result<u64, DbErr>> + Send>>;
+
+/// Rows of a list query on the requested page
+#[derive(SimpleObject)]
+#[graphql(complex)]
+#[graphql(concrete(name = "LedgerPage", params(ledger::Model)))]
+#[graphql(concrete(name = "AccountPage", params(account::Model)))]
+#[graphql(concrete(name = "ContractPage", params(contract::Model)))]
+#[graphql(concrete(name = "EventPage", params(event::Model)))]
+#[graphql(concrete(name = "TransactionPage", params(transaction::Model)))]
+#[graphql(concrete(name = "OperationPage", params(operation::Model)))]
+#[graphql(concrete(name = "UpgradePage", params(upgrade::Model)))]
+#[graphql(concrete(name = "QuorumSetPage", params(quorum_set::Model)))]
+pub struct Page<T: OutputType> {
+    pub items: Vec<T>,
+    /// Whether rows remain after this page
+    pub has_next_page: bool,
+    /// Counts every row of the query, only run when the total is requested
+    #[graphql(skip)]
+    count: Box<dyn Fn(DatabaseConnection) -> CountFuture + Send + Sync>,
+    /// Unpaginated query, explained to estimate the total
+    #[graphql(skip)]
+    statement: Statement,
+}
+
+#[ComplexObject]
+impl<T: OutputType> Page<T> {
+    /// Number of rows across every page, `estimated` reads the planner
+    /// statistics instead of counting, which is much cheaper on large tables
+    async fn total_count<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        #[graphql(default)] estimated: bool,
+    ) -> async_graphql::Result<u64> {
+        let database = ctx
+            .data::<DatabaseConnection>()
+            .expect("DatabaseConnection missing from GraphQL context");
+
+        if estimated && self.statement.db_backend == DbBackend::Postgres {
+            return Ok(estimate_count(database, &self.statement).await?);
+        }
+
+        Ok((self.count)(database.clone()).await?)
+    }
+}
+
+/// Runs the query on the requested page, or the first `DEFAULT_PER_PAGE` rows,
+/// fetching one more row to tell whether a next page exists
+pub async fn paginate<E>(
+    database: &DatabaseConnection,
+    query: Select<E>,
+    pagination: Option<Pagination>,
+) -> Result<Page<E::Model>, Error>
+where
+    E: EntityTrait,
+    E::Model: OutputType,
+{
+    let statement = query.build(database.get_database_backend());
+    let count_query = query.clone();
+    let count = Box::new(move |database: DatabaseConnection| -> CountFuture {
+        let query = count_query.clone();
+        Box::pin(async move { query.count(&database).await })
+    });
+
+    let (offset, per_page) = match &pagination {
+        Some(pagination) => pagination.bounds()?,
+        None => (0, DEFAULT_PER_PAGE),
+    };
+    let mut items = query
+        .offset(offset)
+        .limit(per_page + 1)
+        .all(database)
+        .await?;
+
+    let has_next_page = items.len() as u64 > per_page;
+    items.truncate(per_page as usize);
+
+    Ok(Page {
+        items,
+        has_next_page,
+        count,
+        statement,
+    })
+}
+
+/// Reads the number of rows the query planner expects the statement to return
+async fn estimate_count(
+    database: &DatabaseConnection,
+    statement: &Statement,
+) -> Result<u64, DbErr> {
+    let explain = Statement {
+        sql: format!("EXPLAIN (FORMAT JSON) {}", statement.sql),
+        values: statement.values.clone(),
+        db_backend: statement.db_backend,
+    };
+
+    let plan = match database.query_one(explain).await? {
+        Some(row) => row.try_get::<serde_json::Value>("", "QUERY PLAN")?,
+        None => return Ok(0),
+    };
+
+    Ok(plan[0]["Plan"]["Plan Rows"].as_f64().unwrap_or(0.0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_reject_page_zero() {
+        let pagination = Pagination {
+            page: 0,
+            per_page: 10,
+        };
+        assert!(pagination.bounds().is_err());
+    }
+
+    #[test]
+    fn bounds_clamp_per_page() {
+        let pagination = Pagination {
+            page: 3,
+            per_page: u64::MAX,
+        };
+        assert_eq!(
+            pagination.bounds().unwrap(),
+            (2 * MAX_PER_PAGE, MAX_PER_PAGE)
+        );
+
+        let pagination = Pagination {
+            page: u64::MAX,
+            per_page: 10,
+        };
+        assert!(pagination.bounds().is_err());
+    }
+}
@@ -1,6 +1,17 @@
 pub use super::account::Entity as Account;
+pub use super::account_data::Entity as AccountData;
+pub use super::account_history::Entity as AccountHistory;
+pub use super::account_signer::Entity as AccountSigner;
+pub use super::api_key::Entity as ApiKey;
+pub use super::backfill::Entity as Backfill;
 pub use super::contract::Entity as Contract;
 pub use super::event::Entity as Event;
 pub use super::ledger::Entity as Ledger;
+pub use super::network_config::Entity as NetworkConfig;
 pub use super::operation::Entity as Operation;
+pub use super::quorum_node::Entity as QuorumNode;
+pub use super::quorum_set::Entity as QuorumSet;
 pub use super::transaction::Entity as Transaction;
+pub use super::upgrade::Entity as Upgrade;
+pub use super::webhook::Entity as Webhook;
+pub use super::webhook_dead_letter::Entity as WebhookDeadLetter;
@@ -0,0 +1,92 @@
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context,
+};
+use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, QueryOrder, Set};
+use std::{collections::HashMap, sync::Arc};
+use stellar_node_entities::quoruminfo;
+
+use crate::{
+    quorum_set::{self, QuorumSetHash},
+    QuasarDataLoader,
+};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, async_graphql::SimpleObject)]
+#[sea_orm(table_name = "quorum_nodes")]
+#[graphql(complex)]
+#[graphql(name = "QuorumNodes")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub node_id: String,
+    pub quorum_set_hash: String,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::quorum_set::Entity",
+        from = "Column::QuorumSetHash",
+        to = "super::quorum_set::Column::Hash"
+    )]
+    QuorumSet,
+}
+
+impl Related<super::quorum_set::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::QuorumSet.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[ComplexObject]
+impl Model {
+    pub async fn quorum_set<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Option<quorum_set::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        Ok(loader
+            .load_one(QuorumSetHash(self.quorum_set_hash.clone()))
+            .await?)
+    }
+}
+
+impl From<quoruminfo::Model> for ActiveModel {
+    fn from(quoruminfo: quoruminfo::Model) -> Self {
+        Self {
+            node_id: Set(quoruminfo.nodeid),
+            quorum_set_hash: Set(quoruminfo.qsethash),
+            created_at: NotSet,
+        }
+    }
+}
+
+/// Nodes using a quorum set, keyed by quorum set hash
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct QuorumSetNodes(pub String);
+
+#[async_trait::async_trait]
+impl Loader<QuorumSetNodes> for QuasarDataLoader {
+    type Value = Vec<Model>;
+    type Error = Arc<DbErr>;
+
+    async fn load(
+        &self,
+        keys: &[QuorumSetNodes],
+    ) -> Result<HashMap<QuorumSetNodes, Self::Value>, Self::Error> {
+        let mut condition = Condition::any();
+
+        for QuorumSetNodes(key) in keys {
+            condition = condition.add(Column::QuorumSetHash.eq(key.clone()));
+        }
+        let query = Entity::find()
+            .filter(condition)
+            .order_by_asc(Column::NodeId);
+        self.load_grouped(query, |model| QuorumSetNodes(model.quorum_set_hash.clone()))
+            .await
+    }
+}
@@ -0,0 +1,143 @@
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context,
+};
+use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, Set};
+use serde_json::{json, Value};
+use std::{collections::HashMap, sync::Arc};
+use stellar_node_entities::scpquorums;
+use stellar_strkey::ed25519::PublicKey as StrkeyPublicKey;
+use stellar_xdr::curr::{Error, Limits, NodeId, PublicKey, ReadXdr, ScpQuorumSet};
+
+use crate::{
+    quorum_node::{self, QuorumSetNodes},
+    QuasarDataLoader,
+};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, async_graphql::SimpleObject)]
+#[sea_orm(table_name = "quorum_sets")]
+#[graphql(complex)]
+#[graphql(name = "QuorumSets")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub hash: String,
+    /// Last ledger in which the Stellar node saw the quorum set
+    pub last_ledger_sequence: i32,
+    pub threshold: i32,
+    /// Validator public keys
+    pub validators: Json,
+    /// Nested quorum sets, each with a `threshold`, `validators` and `innerSets`
+    pub inner_sets: Json,
+    /// Base64 encoded `ScpQuorumSet` XDR
+    pub raw_quorum_set: String,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        has_many = "super::quorum_node::Entity",
+        to = "super::quorum_node::Column::QuorumSetHash",
+        from = "Column::Hash"
+    )]
+    QuorumNode,
+}
+
+impl Related<super::quorum_node::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::QuorumNode.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[ComplexObject]
+impl Model {
+    /// Nodes of the network using this quorum set
+    pub async fn nodes<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Vec<quorum_node::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        Ok(loader
+            .load_one(QuorumSetNodes(self.hash.clone()))
+            .await?
+            .unwrap_or_default())
+    }
+}
+
+impl TryFrom<scpquorums::Model> for ActiveModel {
+    type Error = Error;
+
+    fn try_from(scpquorums: scpquorums::Model) -> Result<Self, Self::Error> {
+        let quorum_set = ScpQuorumSet::from_xdr_base64(&scpquorums.qset, Limits::none())?;
+
+        Ok(Self {
+            hash: Set(scpquorums.qsethash),
+            last_ledger_sequence: Set(scpquorums.lastledgerseq),
+            threshold: Set(quorum_set.threshold as i32),
+            validators: Set(validators_to_json(&quorum_set)),
+            inner_sets: Set(inner_sets_to_json(&quorum_set)),
+            raw_quorum_set: Set(scpquorums.qset),
+            created_at: NotSet,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct QuorumSetHash(pub String);
+
+#[async_trait::async_trait]
+impl Loader<QuorumSetHash> for QuasarDataLoader {
+    type Value = Model;
+    type Error = Arc<DbErr>;
+
+    async fn load(
+        &self,
+        keys: &[QuorumSetHash],
+    ) -> Result<HashMap<QuorumSetHash, Self::Value>, Self::Error> {
+        let mut condition = Condition::any();
+
+        for QuorumSetHash(hash) in keys {
+            condition = condition.add(Column::Hash.eq(hash.clone()));
+        }
+        let quorum_sets = Entity::find()
+            .filter(condition)
+            .all(&self.pool)
+            .await
+            .map_err(Arc::new)?;
+        Ok(quorum_sets
+            .into_iter()
+            .map(|quorum_set| (QuorumSetHash(quorum_set.hash.clone()), quorum_set))
+            .collect())
+    }
+}
+
+pub fn node_id_to_string(node_id: &NodeId) -> String {
+    let NodeId(PublicKey::PublicKeyTypeEd25519(key)) = node_id;
+    StrkeyPublicKey(key.0).to_string()
+}
+
+fn validators_to_json(quorum_set: &ScpQuorumSet) -> Value {
+    quorum_set
+        .validators
+        .iter()
+        .map(node_id_to_string)
+        .collect()
+}
+
+fn inner_sets_to_json(quorum_set: &ScpQuorumSet) -> Value {
+    quorum_set
+        .inner_sets
+        .iter()
+        .map(|inner_set| {
+            json!({
+                "threshold": inner_set.threshold,
+                "validators": validators_to_json(inner_set),
+                "innerSets": inner_sets_to_json(inner_set),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,315 @@
+use crate::{account, contract, event, ledger, operation, transaction, upgrade};
+use async_graphql::{Enum, OneofObject};
+use sea_orm::{EntityTrait, IdenStatic, Iterable, Order, PrimaryKeyToColumn, QueryOrder, Select};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl From<SortOrder> for Order {
+    fn from(val: SortOrder) -> Self {
+        match val {
+            SortOrder::Asc => Self::Asc,
+            SortOrder::Desc => Self::Desc,
+        }
+    }
+}
+
+/// Sort input selecting a single column of an entity
+pub trait SortColumn {
+    type Entity: EntityTrait;
+
+    fn column(&self) -> (<Self::Entity as EntityTrait>::Column, SortOrder);
+}
+
+/// Columns of the sort in turn, falling back on the default order, then the
+/// primary key so rows with equal values keep a stable order across pages
+pub fn sort_columns<S: SortColumn>(
+    sort: Option<Vec<S>>,
+    default: &[(<S::Entity as EntityTrait>::Column, Order)],
+) -> Vec<(<S::Entity as EntityTrait>::Column, Order)> {
+    let mut columns: Vec<_> = match sort {
+        Some(sort) if !sort.is_empty() => sort
+            .iter()
+            .map(|sort| {
+                let (column, order) = sort.column();
+                (column, order.into())
+            })
+            .collect(),
+        _ => default.to_vec(),
+    };
+
+    let tie_breaker = match columns.last() {
+        Some((_, order)) => order.clone(),
+        None => Order::Asc,
+    };
+    for key in <S::Entity as EntityTrait>::PrimaryKey::iter() {
+        let key = key.into_column();
+        if !columns
+            .iter()
+            .any(|(column, _)| column.as_str() == key.as_str())
+        {
+            columns.push((key, tie_breaker.clone()));
+        }
+    }
+
+    columns
+}
+
+/// Orders the query by the columns of the sort, see [`sort_columns`]
+pub fn apply_sort<S: SortColumn>(
+    query: Select<S::Entity>,
+    sort: Option<Vec<S>>,
+    default: &[(<S::Entity as EntityTrait>::Column, Order)],
+) -> Select<S::Entity> {
+    let mut query = query;
+    for (column, order) in sort_columns(sort, default) {
+        query = query.order_by(column, order);
+    }
+    query
+}
+
+#[derive(OneofObject)]
+pub enum LedgerSort {
+    Hash(SortOrder),
+    PreviousLedgerHash(SortOrder),
+    ProtocolVersion(SortOrder),
+    Sequence(SortOrder),
+    TotalCoins(SortOrder),
+    FeePool(SortOrder),
+    InflationSeq(SortOrder),
+    IdPool(SortOrder),
+    BaseFee(SortOrder),
+    BaseReserve(SortOrder),
+    MaxTxSetSize(SortOrder),
+    ClosedAt(SortOrder),
+    TxSetHash(SortOrder),
+    TransactionCount(SortOrder),
+    SuccessfulTransactionCount(SortOrder),
+    FailedTransactionCount(SortOrder),
+    OperationCount(SortOrder),
+    CreatedAt(SortOrder),
+}
+
+impl SortColumn for LedgerSort {
+    type Entity = ledger::Entity;
+
+    fn column(&self) -> (ledger::Column, SortOrder) {
+        match self {
+            Self::Hash(order) => (ledger::Column::Hash, *order),
+            Self::PreviousLedgerHash(order) => (ledger::Column::PreviousLedgerHash, *order),
+            Self::ProtocolVersion(order) => (ledger::Column::ProtocolVersion, *order),
+            Self::Sequence(order) => (ledger::Column::Sequence, *order),
+            Self::TotalCoins(order) => (ledger::Column::TotalCoins, *order),
+            Self::FeePool(order) => (ledger::Column::FeePool, *order),
+            Self::InflationSeq(order) => (ledger::Column::InflationSeq, *order),
+            Self::IdPool(order) => (ledger::Column::IdPool, *order),
+            Self::BaseFee(order) => (ledger::Column::BaseFee, *order),
+            Self::BaseReserve(order) => (ledger::Column::BaseReserve, *order),
+            Self::MaxTxSetSize(order) => (ledger::Column::MaxTxSetSize, *order),
+            Self::ClosedAt(order) => (ledger::Column::ClosedAt, *order),
+            Self::TxSetHash(order) => (ledger::Column::TxSetHash, *order),
+            Self::TransactionCount(order) => (ledger::Column::TransactionCount, *order),
+            Self::SuccessfulTransactionCount(order) => {
+                (ledger::Column::SuccessfulTransactionCount, *order)
+            }
+            Self::FailedTransactionCount(order) => (ledger::Column::FailedTransactionCount, *order),
+            Self::OperationCount(order) => (ledger::Column::OperationCount, *order),
+            Self::CreatedAt(order) => (ledger::Column::CreatedAt, *order),
+        }
+    }
+}
+
+#[derive(OneofObject)]
+pub enum AccountSort {
+    Id(SortOrder),
+    Balance(SortOrder),
+    BuyingLiabilities(SortOrder),
+    SellingLiabilities(SortOrder),
+    SequenceNumber(SortOrder),
+    NumberOfSubentries(SortOrder),
+    InflationDestination(SortOrder),
+    HomeDomain(SortOrder),
+    MasterWeight(SortOrder),
+    ThresholdLow(SortOrder),
+    ThresholdMedium(SortOrder),
+    ThresholdHigh(SortOrder),
+    LastModified(SortOrder),
+    Flags(SortOrder),
+    Sponsor(SortOrder),
+    NumberOfSponsored(SortOrder),
+    NumberOfSponsoring(SortOrder),
+    SequenceLedger(SortOrder),
+    SequenceTime(SortOrder),
+    DeletedInLedger(SortOrder),
+    CreatedAt(SortOrder),
+}
+
+impl SortColumn for AccountSort {
+    type Entity = account::Entity;
+
+    fn column(&self) -> (account::Column, SortOrder) {
+        match self {
+            Self::Id(order) => (account::Column::Id, *order),
+            Self::Balance(order) => (account::Column::Balance, *order),
+            Self::BuyingLiabilities(order) => (account::Column::BuyingLiabilities, *order),
+            Self::SellingLiabilities(order) => (account::Column::SellingLiabilities, *order),
+            Self::SequenceNumber(order) => (account::Column::SequenceNumber, *order),
+            Self::NumberOfSubentries(order) => (account::Column::NumberOfSubentries, *order),
+            Self::InflationDestination(order) => (account::Column::InflationDestination, *order),
+            Self::HomeDomain(order) => (account::Column::HomeDomain, *order),
+            Self::MasterWeight(order) => (account::Column::MasterWeight, *order),
+            Self::ThresholdLow(order) => (account::Column::ThresholdLow, *order),
+            Self::ThresholdMedium(order) => (account::Column::ThresholdMedium, *order),
+            Self::ThresholdHigh(order) => (account::Column::ThresholdHigh, *order),
+            Self::LastModified(order) => (account::Column::LastModified, *order),
+            Self::Flags(order) => (account::Column::Flags, *order),
+            Self::Sponsor(order) => (account::Column::Sponsor, *order),
+            Self::NumberOfSponsored(order) => (account::Column::NumberOfSponsored, *order),
+            Self::NumberOfSponsoring(order) => (account::Column::NumberOfSponsoring, *order),
+            Self::SequenceLedger(order) => (account::Column::SequenceLedger, *order),
+            Self::SequenceTime(order) => (account::Column::SequenceTime, *order),
+            Self::DeletedInLedger(order) => (account::Column::DeletedInLedger, *order),
+            Self::CreatedAt(order) => (account::Column::CreatedAt, *order),
+        }
+    }
+}
+
+#[derive(OneofObject)]
+pub enum ContractSort {
+    Address(SortOrder),
+    Hash(SortOrder),
+    Key(SortOrder),
+    Type(SortOrder),
+    LastModified(SortOrder),
+    CreatedAt(SortOrder),
+}
+
+impl SortColumn for ContractSort {
+    type Entity = contract::Entity;
+
+    fn column(&self) -> (contract::Column, SortOrder) {
+        match self {
+            Self::Address(order) => (contract::Column::Address, *order),
+            Self::Hash(order) => (contract::Column::Hash, *order),
+            Self::Key(order) => (contract::Column::Key, *order),
+            Self::Type(order) => (contract::Column::Type, *order),
+            Self::LastModified(order) => (contract::Column::LastModified, *order),
+            Self::CreatedAt(order) => (contract::Column::CreatedAt, *order),
+        }
+    }
+}
+
+#[derive(OneofObject)]
+pub enum EventSort {
+    Id(SortOrder),
+    Topic(SortOrder),
+    ContractId(SortOrder),
+    TransactionId(SortOrder),
+    Type(SortOrder),
+    ClosedAt(SortOrder),
+    CreatedAt(SortOrder),
+}
+
+impl SortColumn for EventSort {
+    type Entity = event::Entity;
+
+    fn column(&self) -> (event::Column, SortOrder) {
+        match self {
+            Self::Id(order) => (event::Column::Id, *order),
+            Self::Topic(order) => (event::Column::Topic, *order),
+            Self::ContractId(order) => (event::Column::ContractId, *order),
+            Self::TransactionId(order) => (event::Column::TransactionId, *order),
+            Self::Type(order) => (event::Column::Type, *order),
+            Self::ClosedAt(order) => (event::Column::ClosedAt, *order),
+            Self::CreatedAt(order) => (event::Column::CreatedAt, *order),
+        }
+    }
+}
+
+#[derive(OneofObject)]
+pub enum OperationSort {
+    Id(SortOrder),
+    TransactionId(SortOrder),
+    ApplicationOrder(SortOrder),
+    Type(SortOrder),
+    ClosedAt(SortOrder),
+    CreatedAt(SortOrder),
+}
+
+impl SortColumn for OperationSort {
+    type Entity = operation::Entity;
+
+    fn column(&self) -> (operation::Column, SortOrder) {
+        match self {
+            Self::Id(order) => (operation::Column::Id, *order),
+            Self::TransactionId(order) => (operation::Column::TransactionId, *order),
+            Self::ApplicationOrder(order) => (operation::Column::ApplicationOrder, *order),
+            Self::Type(order) => (operation::Column::Type, *order),
+            Self::ClosedAt(order) => (operation::Column::ClosedAt, *order),
+            Self::CreatedAt(order) => (operation::Column::CreatedAt, *order),
+        }
+    }
+}
+
+#[derive(OneofObject)]
+pub enum TransactionSort {
+    Id(SortOrder),
+    LedgerSequence(SortOrder),
+    ApplicationOrder(SortOrder),
+    AccountId(SortOrder),
+    AccountSequence(SortOrder),
+    OperationCount(SortOrder),
+    ClosedAt(SortOrder),
+    FeeCharged(SortOrder),
+    Successful(SortOrder),
+    CreatedAt(SortOrder),
+}
+
+impl SortColumn for TransactionSort {
+    type Entity = transaction::Entity;
+
+    fn column(&self) -> (transaction::Column, SortOrder) {
+        match self {
+            Self::Id(order) => (transaction::Column::Id, *order),
+            Self::LedgerSequence(order) => (transaction::Column::LedgerSequence, *order),
+            Self::ApplicationOrder(order) => (transaction::Column::ApplicationOrder, *order),
+            Self::AccountId(order) => (transaction::Column::AccountId, *order),
+            Self::AccountSequence(order) => (transaction::Column::AccountSequence, *order),
+            Self::OperationCount(order) => (transaction::Column::OperationCount, *order),
+            Self::ClosedAt(order) => (transaction::Column::ClosedAt, *order),
+            Self::FeeCharged(order) => (transaction::Column::FeeCharged, *order),
+            Self::Successful(order) => (transaction::Column::Successful, *order),
+            Self::CreatedAt(order) => (transaction::Column::CreatedAt, *order),
+        }
+    }
+}
+
+#[derive(OneofObject)]
+pub enum UpgradeSort {
+    LedgerSequence(SortOrder),
+    UpgradeIndex(SortOrder),
+    Type(SortOrder),
+    Value(SortOrder),
+    ConfigContract(SortOrder),
+    ConfigContentHash(SortOrder),
+    CreatedAt(SortOrder),
+}
+
+impl SortColumn for UpgradeSort {
+    type Entity = upgrade::Entity;
+
+    fn column(&self) -> (upgrade::Column, SortOrder) {
+        match self {
+            Self::LedgerSequence(order) => (upgrade::Column::LedgerSequence, *order),
+            Self::UpgradeIndex(order) => (upgrade::Column::UpgradeIndex, *order),
+            Self::Type(order) => (upgrade::Column::Type, *order),
+            Self::Value(order) => (upgrade::Column::Value, *order),
+            Self::ConfigContract(order) => (upgrade::Column::ConfigContract, *order),
+            Self::ConfigContentHash(order) => (upgrade::Column::ConfigContentHash, *order),
+            Self::CreatedAt(order) => (upgrade::Column::CreatedAt, *order),
+        }
+    }
+}
@@ -1,12 +1,25 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use async_graphql::{dataloader::Loader, ComplexObject, Context};
-use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, Set};
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context,
+};
+use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, Order, Set};
 use stellar_strkey::ed25519::PublicKey;
 use stellar_xdr::curr::{Error, FeeBumpTransactionInnerTx, MuxedAccount, TransactionEnvelope};
 
-use crate::{account, event, operation, QuasarDataLoader};
+use crate::{
+    account::AccountId,
+    children::{ChildRelation, Children},
+    event::{self, TransactionEvents},
+    filter::{EventFilter, OperationFilter},
+    ledger::{self, LedgerSequence},
+    operation::{self, TransactionOperations},
+    pagination::Pagination,
+    sort::{sort_columns, EventSort, OperationSort},
+    QuasarDataLoader,
+};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, async_graphql::SimpleObject)]
 #[sea_orm(table_name = "transactions")]
@@ -20,6 +33,12 @@ pub struct Model {
     pub account_id: String,
     pub account_sequence: i64,
     pub operation_count: i32,
+    /// Close time of the ledger
+    pub closed_at: Option<DateTimeWithTimeZone>,
+    /// Fee charged in stroops, `None` for transactions ingested before it was stored
+    pub fee_charged: Option<i64>,
+    /// Whether the transaction succeeded, `None` for transactions ingested before it was stored
+    pub successful: Option<bool>,
     pub created_at: DateTimeWithTimeZone,
 }
 
@@ -43,6 +62,12 @@ pub enum Relation {
         from = "Column::Id"
     )]
     Event,
+    #[sea_orm(
+        belongs_to = "super::ledger::Entity",
+        from = "Column::LedgerSequence",
+        to = "super::ledger::Column::Sequence"
+    )]
+    Ledger,
 }
 
 impl Related<super::operation::Entity> for Entity {
@@ -63,6 +88,12 @@ impl Related<super::event::Entity> for Entity {
     }
 }
 
+impl Related<super::ledger::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Ledger.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
 #[ComplexObject]
@@ -70,28 +101,60 @@ impl Model {
     pub async fn account<'ctx>(
         &self,
         ctx: &Context<'ctx>,
-    ) -> Result<Option<super::account::Model>, DbErr> {
-        let database = ctx
-            .data::<DatabaseConnection>()
-            .expect("DatabaseConnection missing from GraphQL context");
-        self.find_related(account::Entity).one(database).await
+    ) -> async_graphql::Result<Option<super::account::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        Ok(loader.load_one(AccountId(self.account_id.clone())).await?)
     }
 
+    pub async fn ledger<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Option<ledger::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        Ok(loader
+            .load_one(LedgerSequence(self.ledger_sequence))
+            .await?)
+    }
+
+    /// Operations of this transaction, in application order by default
     pub async fn operations<'ctx>(
         &self,
         ctx: &Context<'ctx>,
-    ) -> Result<Vec<operation::Model>, DbErr> {
-        let database = ctx
-            .data::<DatabaseConnection>()
-            .expect("DatabaseConnection missing from GraphQL context");
-        self.find_related(operation::Entity).all(database).await
+        filter: Option<OperationFilter>,
+        sort: Option<Vec<OperationSort>>,
+        pagination: Option<Pagination>,
+    ) -> async_graphql::Result<Vec<operation::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let query = TransactionOperations::query();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+        let order = sort_columns(sort, &[(operation::Column::ApplicationOrder, Order::Asc)]);
+        let key =
+            Children::<TransactionOperations>::new(self.id.clone(), query, order, pagination)?;
+        Ok(loader.load_one(key).await?.unwrap_or_default())
     }
 
-    pub async fn events<'ctx>(&self, ctx: &Context<'ctx>) -> Result<Vec<event::Model>, DbErr> {
-        let database = ctx
-            .data::<DatabaseConnection>()
-            .expect("DatabaseConnection missing from GraphQL context");
-        self.find_related(event::Entity).all(database).await
+    /// Events emitted by this transaction, in emission order by default
+    pub async fn events<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+        filter: Option<EventFilter>,
+        sort: Option<Vec<EventSort>>,
+        pagination: Option<Pagination>,
+    ) -> async_graphql::Result<Vec<event::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        let query = TransactionEvents::query();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+        let order = sort_columns(sort, &[(event::Column::Id, Order::Asc)]);
+        let key = Children::<TransactionEvents>::new(self.id.clone(), query, order, pagination)?;
+        Ok(loader.load_one(key).await?.unwrap_or_default())
     }
 }
 
@@ -142,6 +205,9 @@ impl TryFrom<TransactionEnvelope> for ActiveModel {
             account_id: Set(account_str_key.to_string()),
             account_sequence: Set(seq_num),
             operation_count: Set(operation_count),
+            closed_at: NotSet,
+            fee_charged: NotSet,
+            successful: NotSet,
             created_at: NotSet,
         })
     }
@@ -175,3 +241,37 @@ impl Loader<TransactionId> for QuasarDataLoader {
             .collect())
     }
 }
+
+/// Transactions of a ledger, keyed by ledger sequence
+pub struct LedgerTransactions;
+
+impl ChildRelation for LedgerTransactions {
+    type Entity = Entity;
+    type Holder = Entity;
+    type Parent = i32;
+
+    fn query() -> Select<Entity> {
+        Entity::find()
+    }
+
+    fn parent() -> Column {
+        Column::LedgerSequence
+    }
+}
+
+/// Transactions submitted by an account, keyed by account id
+pub struct AccountTransactions;
+
+impl ChildRelation for AccountTransactions {
+    type Entity = Entity;
+    type Holder = Entity;
+    type Parent = String;
+
+    fn query() -> Select<Entity> {
+        Entity::find()
+    }
+
+    fn parent() -> Column {
+        Column::AccountId
+    }
+}
@@ -0,0 +1,128 @@
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context,
+};
+use base64::{engine::general_purpose, Engine};
+use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Condition, QueryOrder, Set};
+use std::{collections::HashMap, sync::Arc};
+use stellar_node_entities::upgradehistory;
+use stellar_strkey::Contract;
+use stellar_xdr::curr::{Error, LedgerUpgrade, Limits, ReadXdr};
+
+use crate::{
+    ledger::{self, LedgerSequence},
+    QuasarDataLoader,
+};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, async_graphql::SimpleObject)]
+#[sea_orm(table_name = "upgrades")]
+#[graphql(complex)]
+#[graphql(name = "Upgrades")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub ledger_sequence: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub upgrade_index: i32,
+    pub r#type: String,
+    /// New value of a protocol version, fee, reserve, size or flags upgrade
+    pub value: Option<i64>,
+    /// Contract holding the Soroban config upgrade set
+    pub config_contract: Option<String>,
+    /// Base64 encoded hash of the Soroban config upgrade set
+    pub config_content_hash: Option<String>,
+    /// Base64 encoded `LedgerUpgrade` XDR
+    pub raw_upgrade: String,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::ledger::Entity",
+        from = "Column::LedgerSequence",
+        to = "super::ledger::Column::Sequence"
+    )]
+    Ledger,
+}
+
+impl Related<super::ledger::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Ledger.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[ComplexObject]
+impl Model {
+    pub async fn ledger<'ctx>(
+        &self,
+        ctx: &Context<'ctx>,
+    ) -> async_graphql::Result<Option<ledger::Model>> {
+        let loader = ctx
+            .data::<DataLoader<QuasarDataLoader>>()
+            .expect("DataLoader missing from GraphQL context");
+        Ok(loader
+            .load_one(LedgerSequence(self.ledger_sequence))
+            .await?)
+    }
+}
+
+impl TryFrom<upgradehistory::Model> for ActiveModel {
+    type Error = Error;
+
+    fn try_from(upgradehistory: upgradehistory::Model) -> Result<Self, Self::Error> {
+        let upgrade = LedgerUpgrade::from_xdr_base64(&upgradehistory.upgrade, Limits::none())?;
+
+        let (value, config_contract, config_content_hash) = match &upgrade {
+            LedgerUpgrade::Version(value)
+            | LedgerUpgrade::BaseFee(value)
+            | LedgerUpgrade::MaxTxSetSize(value)
+            | LedgerUpgrade::BaseReserve(value)
+            | LedgerUpgrade::Flags(value)
+            | LedgerUpgrade::MaxSorobanTxSetSize(value) => (Some(*value as i64), None, None),
+            LedgerUpgrade::Config(key) => (
+                None,
+                Some(Contract(key.contract_id.0).to_string()),
+                Some(general_purpose::STANDARD.encode(key.content_hash.0)),
+            ),
+        };
+
+        Ok(Self {
+            ledger_sequence: Set(upgradehistory.ledgerseq),
+            upgrade_index: Set(upgradehistory.upgradeindex),
+            r#type: Set(upgrade.name().to_string()),
+            value: Set(value),
+            config_contract: Set(config_contract),
+            config_content_hash: Set(config_content_hash),
+            raw_upgrade: Set(upgradehistory.upgrade),
+            created_at: NotSet,
+        })
+    }
+}
+
+/// Upgrades applied at the end of a ledger, keyed by ledger sequence
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LedgerUpgrades(pub i32);
+
+#[async_trait::async_trait]
+impl Loader<LedgerUpgrades> for QuasarDataLoader {
+    type Value = Vec<Model>;
+    type Error = Arc<DbErr>;
+
+    async fn load(
+        &self,
+        keys: &[LedgerUpgrades],
+    ) -> Result<HashMap<LedgerUpgrades, Self::Value>, Self::Error> {
+        let mut condition = Condition::any();
+
+        for LedgerUpgrades(key) in keys {
+            condition = condition.add(Column::LedgerSequence.eq(*key));
+        }
+        let query = Entity::find()
+            .filter(condition)
+            .order_by_asc(Column::UpgradeIndex);
+        self.load_grouped(query, |model| LedgerUpgrades(model.ledger_sequence))
+            .await
+    }
+}
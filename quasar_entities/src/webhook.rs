@@ -0,0 +1,38 @@
+use sea_orm::entity::prelude::*;
+
+/// Webhooks are managed from the configuration file and the command line
+/// only, they are not exposed over the API
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "webhooks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub url: String,
+    /// Key used to sign the payloads
+    pub secret: String,
+    pub contract_id: Option<String>,
+    pub topic: Option<String>,
+    pub r#type: Option<String>,
+    /// Id of the last event delivered or dead lettered, events are read from
+    /// the database after it so none is lost across restarts
+    pub last_event_id: Option<i32>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        has_many = "super::webhook_dead_letter::Entity",
+        to = "super::webhook_dead_letter::Column::WebhookId",
+        from = "Column::Id"
+    )]
+    WebhookDeadLetter,
+}
+
+impl Related<super::webhook_dead_letter::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WebhookDeadLetter.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
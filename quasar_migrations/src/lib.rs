@@ -7,6 +7,31 @@ mod m20231016_094252_create_transactions;
 mod m20231016_094302_create_operations;
 mod m20231018_125257_create_events_table;
 mod m20231026_092149_created_at_cursor_on_tables;
+mod m20231101_103015_create_account_data;
+mod m20231102_081544_add_account_flags_and_sponsorship;
+mod m20231102_082210_create_account_signers;
+mod m20231103_141032_add_account_deleted_in_ledger;
+mod m20231106_093327_create_account_history;
+mod m20231107_140512_create_upgrades;
+mod m20231108_101244_create_network_config;
+mod m20231109_090417_add_ledger_close_metadata;
+mod m20231110_083951_closed_at_on_tables;
+mod m20231113_102730_create_quorum_sets;
+mod m20231116_135218_create_webhooks;
+mod m20231120_094610_add_event_xdr;
+mod m20231121_103204_create_string_filter_indexes;
+mod m20231122_141507_index_event_values;
+mod m20231124_101532_add_transaction_results;
+mod m20231127_093412_create_api_keys;
+mod m20231128_104517_add_webhook_cursors;
+mod m20231128_151203_create_backfills;
+mod m20231129_091522_backfill_closed_at;
+mod m20231129_143307_backfill_transaction_results;
+mod m20231130_102214_backfill_event_xdr;
+mod m20231130_153840_create_string_pattern_indexes;
+mod m20231201_094405_add_operation_contract;
+mod m20231201_141208_load_node_state;
+mod m20231202_090312_backfill_ledger_close_metadata;
 
 pub struct Migrator;
 
@@ -21,6 +46,31 @@ impl MigratorTrait for Migrator {
             Box::new(m20231016_094252_create_transactions::Migration),
             Box::new(m20231016_094302_create_operations::Migration),
             Box::new(m20231026_092149_created_at_cursor_on_tables::Migration),
+            Box::new(m20231101_103015_create_account_data::Migration),
+            Box::new(m20231102_081544_add_account_flags_and_sponsorship::Migration),
+            Box::new(m20231102_082210_create_account_signers::Migration),
+            Box::new(m20231103_141032_add_account_deleted_in_ledger::Migration),
+            Box::new(m20231106_093327_create_account_history::Migration),
+            Box::new(m20231107_140512_create_upgrades::Migration),
+            Box::new(m20231108_101244_create_network_config::Migration),
+            Box::new(m20231109_090417_add_ledger_close_metadata::Migration),
+            Box::new(m20231110_083951_closed_at_on_tables::Migration),
+            Box::new(m20231113_102730_create_quorum_sets::Migration),
+            Box::new(m20231116_135218_create_webhooks::Migration),
+            Box::new(m20231120_094610_add_event_xdr::Migration),
+            Box::new(m20231121_103204_create_string_filter_indexes::Migration),
+            Box::new(m20231122_141507_index_event_values::Migration),
+            Box::new(m20231124_101532_add_transaction_results::Migration),
+            Box::new(m20231127_093412_create_api_keys::Migration),
+            Box::new(m20231128_104517_add_webhook_cursors::Migration),
+            Box::new(m20231128_151203_create_backfills::Migration),
+            Box::new(m20231129_091522_backfill_closed_at::Migration),
+            Box::new(m20231129_143307_backfill_transaction_results::Migration),
+            Box::new(m20231130_102214_backfill_event_xdr::Migration),
+            Box::new(m20231130_153840_create_string_pattern_indexes::Migration),
+            Box::new(m20231201_094405_add_operation_contract::Migration),
+            Box::new(m20231201_141208_load_node_state::Migration),
+            Box::new(m20231202_090312_backfill_ledger_close_metadata::Migration),
         ]
     }
 }
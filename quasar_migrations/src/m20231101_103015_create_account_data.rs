@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccountData::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(AccountData::AccountId).string().not_null())
+                    .col(ColumnDef::new(AccountData::Name).string().not_null())
+                    .col(ColumnDef::new(AccountData::Value).string())
+                    .col(ColumnDef::new(AccountData::RawValue).string().not_null())
+                    .col(
+                        ColumnDef::new(AccountData::LastModified)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccountData::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(AccountData::AccountId)
+                            .col(AccountData::Name),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccountData::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AccountData {
+    #[sea_orm(iden = "account_data")]
+    Table,
+    AccountId,
+    Name,
+    Value,
+    RawValue,
+    LastModified,
+    CreatedAt,
+}
@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Account::Table)
+                    .add_column(
+                        ColumnDef::new(Account::Flags)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(ColumnDef::new(Account::Sponsor).string())
+                    .add_column(
+                        ColumnDef::new(Account::NumberOfSponsored)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(Account::NumberOfSponsoring)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(ColumnDef::new(Account::SequenceLedger).integer())
+                    .add_column(ColumnDef::new(Account::SequenceTime).big_integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Account::Table)
+                    .drop_column(Account::Flags)
+                    .drop_column(Account::Sponsor)
+                    .drop_column(Account::NumberOfSponsored)
+                    .drop_column(Account::NumberOfSponsoring)
+                    .drop_column(Account::SequenceLedger)
+                    .drop_column(Account::SequenceTime)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Account {
+    #[sea_orm(iden = "accounts")]
+    Table,
+    Flags,
+    Sponsor,
+    NumberOfSponsored,
+    NumberOfSponsoring,
+    SequenceLedger,
+    SequenceTime,
+}
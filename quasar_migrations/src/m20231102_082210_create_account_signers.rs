@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccountSigner::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(AccountSigner::AccountId).string().not_null())
+                    .col(ColumnDef::new(AccountSigner::Signer).string().not_null())
+                    .col(ColumnDef::new(AccountSigner::Type).string().not_null())
+                    .col(ColumnDef::new(AccountSigner::Weight).integer().not_null())
+                    .col(ColumnDef::new(AccountSigner::Sponsor).string())
+                    .col(
+                        ColumnDef::new(AccountSigner::LastModified)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccountSigner::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(AccountSigner::AccountId)
+                            .col(AccountSigner::Signer),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_account_signers_signer")
+                    .table(AccountSigner::Table)
+                    .col(AccountSigner::Signer)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccountSigner::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AccountSigner {
+    #[sea_orm(iden = "account_signers")]
+    Table,
+    AccountId,
+    Signer,
+    Type,
+    Weight,
+    Sponsor,
+    LastModified,
+    CreatedAt,
+}
@@ -0,0 +1,99 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccountHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccountHistory::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AccountHistory::AccountId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccountHistory::LedgerSequence)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AccountHistory::TransactionId).string())
+                    .col(
+                        ColumnDef::new(AccountHistory::Balance)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AccountHistory::BuyingLiabilities).big_integer())
+                    .col(ColumnDef::new(AccountHistory::SellingLiabilities).big_integer())
+                    .col(
+                        ColumnDef::new(AccountHistory::SequenceNumber)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccountHistory::NumberOfSubentries)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccountHistory::Deleted)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(AccountHistory::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_account_history_account_id_ledger_sequence")
+                    .table(AccountHistory::Table)
+                    .col(AccountHistory::AccountId)
+                    .col(AccountHistory::LedgerSequence)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccountHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AccountHistory {
+    #[sea_orm(iden = "account_history")]
+    Table,
+    Id,
+    AccountId,
+    LedgerSequence,
+    TransactionId,
+    Balance,
+    BuyingLiabilities,
+    SellingLiabilities,
+    SequenceNumber,
+    NumberOfSubentries,
+    Deleted,
+    CreatedAt,
+}
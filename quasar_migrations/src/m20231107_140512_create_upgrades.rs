@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Upgrades::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Upgrades::LedgerSequence)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Upgrades::UpgradeIndex).integer().not_null())
+                    .col(ColumnDef::new(Upgrades::Type).string().not_null())
+                    .col(ColumnDef::new(Upgrades::Value).big_integer())
+                    .col(ColumnDef::new(Upgrades::ConfigContract).string())
+                    .col(ColumnDef::new(Upgrades::ConfigContentHash).string())
+                    .col(ColumnDef::new(Upgrades::RawUpgrade).text().not_null())
+                    .col(
+                        ColumnDef::new(Upgrades::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(Upgrades::LedgerSequence)
+                            .col(Upgrades::UpgradeIndex),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Upgrades::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Upgrades {
+    Table,
+    LedgerSequence,
+    UpgradeIndex,
+    Type,
+    Value,
+    ConfigContract,
+    ConfigContentHash,
+    RawUpgrade,
+    CreatedAt,
+}
@@ -0,0 +1,130 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NetworkConfig::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NetworkConfig::LedgerSequence)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(NetworkConfig::ContractMaxSizeBytes).integer())
+                    .col(ColumnDef::new(NetworkConfig::LedgerMaxInstructions).big_integer())
+                    .col(ColumnDef::new(NetworkConfig::TxMaxInstructions).big_integer())
+                    .col(
+                        ColumnDef::new(NetworkConfig::FeeRatePerInstructionsIncrement)
+                            .big_integer(),
+                    )
+                    .col(ColumnDef::new(NetworkConfig::TxMemoryLimit).integer())
+                    .col(ColumnDef::new(NetworkConfig::LedgerMaxReadLedgerEntries).integer())
+                    .col(ColumnDef::new(NetworkConfig::LedgerMaxReadBytes).integer())
+                    .col(ColumnDef::new(NetworkConfig::LedgerMaxWriteLedgerEntries).integer())
+                    .col(ColumnDef::new(NetworkConfig::LedgerMaxWriteBytes).integer())
+                    .col(ColumnDef::new(NetworkConfig::TxMaxReadLedgerEntries).integer())
+                    .col(ColumnDef::new(NetworkConfig::TxMaxReadBytes).integer())
+                    .col(ColumnDef::new(NetworkConfig::TxMaxWriteLedgerEntries).integer())
+                    .col(ColumnDef::new(NetworkConfig::TxMaxWriteBytes).integer())
+                    .col(ColumnDef::new(NetworkConfig::FeeReadLedgerEntry).big_integer())
+                    .col(ColumnDef::new(NetworkConfig::FeeWriteLedgerEntry).big_integer())
+                    .col(ColumnDef::new(NetworkConfig::FeeRead1kb).big_integer())
+                    .col(ColumnDef::new(NetworkConfig::BucketListTargetSizeBytes).big_integer())
+                    .col(ColumnDef::new(NetworkConfig::WriteFee1kbBucketListLow).big_integer())
+                    .col(ColumnDef::new(NetworkConfig::WriteFee1kbBucketListHigh).big_integer())
+                    .col(ColumnDef::new(NetworkConfig::BucketListWriteFeeGrowthFactor).integer())
+                    .col(ColumnDef::new(NetworkConfig::FeeHistorical1kb).big_integer())
+                    .col(ColumnDef::new(NetworkConfig::TxMaxContractEventsSizeBytes).integer())
+                    .col(ColumnDef::new(NetworkConfig::FeeContractEvents1kb).big_integer())
+                    .col(ColumnDef::new(NetworkConfig::LedgerMaxTxsSizeBytes).integer())
+                    .col(ColumnDef::new(NetworkConfig::TxMaxSizeBytes).integer())
+                    .col(ColumnDef::new(NetworkConfig::FeeTxSize1kb).big_integer())
+                    .col(ColumnDef::new(NetworkConfig::ContractDataKeySizeBytes).integer())
+                    .col(ColumnDef::new(NetworkConfig::ContractDataEntrySizeBytes).integer())
+                    .col(ColumnDef::new(NetworkConfig::MaxEntryTtl).integer())
+                    .col(ColumnDef::new(NetworkConfig::MinTemporaryTtl).integer())
+                    .col(ColumnDef::new(NetworkConfig::MinPersistentTtl).integer())
+                    .col(ColumnDef::new(NetworkConfig::PersistentRentRateDenominator).big_integer())
+                    .col(ColumnDef::new(NetworkConfig::TempRentRateDenominator).big_integer())
+                    .col(ColumnDef::new(NetworkConfig::MaxEntriesToArchive).integer())
+                    .col(ColumnDef::new(NetworkConfig::BucketListSizeWindowSampleSize).integer())
+                    .col(ColumnDef::new(NetworkConfig::BucketListWindowSamplePeriod).integer())
+                    .col(ColumnDef::new(NetworkConfig::EvictionScanSize).integer())
+                    .col(ColumnDef::new(NetworkConfig::StartingEvictionScanLevel).integer())
+                    .col(ColumnDef::new(NetworkConfig::LedgerMaxTxCount).integer())
+                    .col(
+                        ColumnDef::new(NetworkConfig::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NetworkConfig::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NetworkConfig {
+    Table,
+    LedgerSequence,
+    ContractMaxSizeBytes,
+    LedgerMaxInstructions,
+    TxMaxInstructions,
+    FeeRatePerInstructionsIncrement,
+    TxMemoryLimit,
+    LedgerMaxReadLedgerEntries,
+    LedgerMaxReadBytes,
+    LedgerMaxWriteLedgerEntries,
+    LedgerMaxWriteBytes,
+    TxMaxReadLedgerEntries,
+    TxMaxReadBytes,
+    TxMaxWriteLedgerEntries,
+    TxMaxWriteBytes,
+    FeeReadLedgerEntry,
+    FeeWriteLedgerEntry,
+    #[sea_orm(iden = "fee_read_1kb")]
+    FeeRead1kb,
+    BucketListTargetSizeBytes,
+    #[sea_orm(iden = "write_fee_1kb_bucket_list_low")]
+    WriteFee1kbBucketListLow,
+    #[sea_orm(iden = "write_fee_1kb_bucket_list_high")]
+    WriteFee1kbBucketListHigh,
+    BucketListWriteFeeGrowthFactor,
+    #[sea_orm(iden = "fee_historical_1kb")]
+    FeeHistorical1kb,
+    TxMaxContractEventsSizeBytes,
+    #[sea_orm(iden = "fee_contract_events_1kb")]
+    FeeContractEvents1kb,
+    LedgerMaxTxsSizeBytes,
+    TxMaxSizeBytes,
+    #[sea_orm(iden = "fee_tx_size_1kb")]
+    FeeTxSize1kb,
+    ContractDataKeySizeBytes,
+    ContractDataEntrySizeBytes,
+    MaxEntryTtl,
+    MinTemporaryTtl,
+    MinPersistentTtl,
+    PersistentRentRateDenominator,
+    TempRentRateDenominator,
+    MaxEntriesToArchive,
+    BucketListSizeWindowSampleSize,
+    BucketListWindowSamplePeriod,
+    EvictionScanSize,
+    StartingEvictionScanLevel,
+    LedgerMaxTxCount,
+    CreatedAt,
+}
@@ -0,0 +1,90 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Ledger::Table)
+                    .add_column(ColumnDef::new(Ledger::ClosedAt).timestamp_with_time_zone())
+                    .add_column(ColumnDef::new(Ledger::TxSetHash).string())
+                    .add_column(
+                        ColumnDef::new(Ledger::TransactionCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(Ledger::SuccessfulTransactionCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(Ledger::FailedTransactionCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(Ledger::OperationCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_ledgers_closed_at")
+                    .table(Ledger::Table)
+                    .col(Ledger::ClosedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_ledgers_closed_at")
+                    .table(Ledger::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Ledger::Table)
+                    .drop_column(Ledger::ClosedAt)
+                    .drop_column(Ledger::TxSetHash)
+                    .drop_column(Ledger::TransactionCount)
+                    .drop_column(Ledger::SuccessfulTransactionCount)
+                    .drop_column(Ledger::FailedTransactionCount)
+                    .drop_column(Ledger::OperationCount)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Ledger {
+    #[sea_orm(iden = "ledgers")]
+    Table,
+    ClosedAt,
+    TxSetHash,
+    TransactionCount,
+    SuccessfulTransactionCount,
+    FailedTransactionCount,
+    OperationCount,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        use ClosedAtIden::*;
+        let tables = [Transactions, Operations, Events];
+        for table in tables {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(
+                            ColumnDef::new(ClosedAtIden::ClosedAt).timestamp_with_time_zone(),
+                        )
+                        .take(),
+                )
+                .await?;
+
+            manager
+                .create_index(
+                    Index::create()
+                        .name(format!("idx_{}_closed_at", table.to_string()))
+                        .table(table)
+                        .col(ClosedAtIden::ClosedAt)
+                        .take(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        use ClosedAtIden::*;
+        let tables = [Transactions, Operations, Events];
+        for table in tables {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(ClosedAtIden::ClosedAt)
+                        .take(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum ClosedAtIden {
+    ClosedAt,
+    Transactions,
+    Operations,
+    Events,
+}
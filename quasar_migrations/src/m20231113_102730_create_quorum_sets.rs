@@ -0,0 +1,95 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(QuorumSets::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(QuorumSets::Hash)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(QuorumSets::LastLedgerSequence)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(QuorumSets::Threshold).integer().not_null())
+                    .col(ColumnDef::new(QuorumSets::Validators).json().not_null())
+                    .col(ColumnDef::new(QuorumSets::InnerSets).json().not_null())
+                    .col(ColumnDef::new(QuorumSets::RawQuorumSet).text().not_null())
+                    .col(
+                        ColumnDef::new(QuorumSets::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(QuorumNodes::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(QuorumNodes::NodeId)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(QuorumNodes::QuorumSetHash)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(QuorumNodes::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(QuorumNodes::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(QuorumSets::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum QuorumSets {
+    Table,
+    Hash,
+    LastLedgerSequence,
+    Threshold,
+    Validators,
+    InnerSets,
+    RawQuorumSet,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum QuorumNodes {
+    Table,
+    NodeId,
+    QuorumSetHash,
+    CreatedAt,
+}
@@ -0,0 +1,120 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Webhooks::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Webhooks::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Webhooks::Url).string().not_null())
+                    .col(ColumnDef::new(Webhooks::Secret).string().not_null())
+                    .col(ColumnDef::new(Webhooks::ContractId).string())
+                    .col(ColumnDef::new(Webhooks::Topic).string())
+                    .col(ColumnDef::new(Webhooks::Type).string())
+                    .col(
+                        ColumnDef::new(Webhooks::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookDeadLetters::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WebhookDeadLetters::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeadLetters::WebhookId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeadLetters::EventId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeadLetters::Payload)
+                            .json()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(WebhookDeadLetters::Error).text().not_null())
+                    .col(
+                        ColumnDef::new(WebhookDeadLetters::Attempts)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeadLetters::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .default(Expr::current_timestamp())
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_dead_letters_webhook_id")
+                            .from(WebhookDeadLetters::Table, WebhookDeadLetters::WebhookId)
+                            .to(Webhooks::Table, Webhooks::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebhookDeadLetters::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Webhooks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Webhooks {
+    Table,
+    Id,
+    Url,
+    Secret,
+    ContractId,
+    Topic,
+    Type,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum WebhookDeadLetters {
+    Table,
+    Id,
+    WebhookId,
+    EventId,
+    Payload,
+    Error,
+    Attempts,
+    CreatedAt,
+}
@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Columns searched with string filters
+const COLUMNS: [(&str, &str); 8] = [
+    ("ledgers", "hash"),
+    ("contracts", "address"),
+    ("contracts", "type"),
+    ("events", "contract_id"),
+    ("events", "topic"),
+    ("events", "type"),
+    ("operations", "type"),
+    ("upgrades", "type"),
+];
+
+/// Primary keys already have an index for exact matches
+const PRIMARY_KEYS: [(&str, &str); 2] = [("ledgers", "hash"), ("contracts", "address")];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (table, column) in COLUMNS {
+            if !PRIMARY_KEYS.contains(&(table, column)) {
+                manager
+                    .create_index(
+                        Index::create()
+                            .if_not_exists()
+                            .name(format!("idx_{}_{}", table, column))
+                            .table(Alias::new(table))
+                            .col(Alias::new(column))
+                            .take(),
+                    )
+                    .await?;
+            }
+
+            // Case-insensitive matches and prefix searches compare the lowercase
+            // value with `LIKE`, which needs the pattern operator class
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    "CREATE INDEX IF NOT EXISTS idx_{table}_{column}_lower_pattern \
+                     ON {table} (lower({column}) text_pattern_ops)"
+                ))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (table, column) in COLUMNS {
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    "DROP INDEX IF EXISTS idx_{table}_{column}_lower_pattern"
+                ))
+                .await?;
+
+            if !PRIMARY_KEYS.contains(&(table, column)) {
+                manager
+                    .drop_index(
+                        Index::drop()
+                            .if_exists()
+                            .name(format!("idx_{}_{}", table, column))
+                            .table(Alias::new(table))
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
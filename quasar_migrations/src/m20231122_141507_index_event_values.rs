@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // GIN indexes and JSON path operators need the binary representation
+        db.execute_unprepared(
+            "ALTER TABLE events ALTER COLUMN value TYPE jsonb USING value::jsonb",
+        )
+        .await?;
+
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_events_value ON events USING GIN (value jsonb_path_ops)",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_events_value")
+            .await?;
+
+        db.execute_unprepared("ALTER TABLE events ALTER COLUMN value TYPE json USING value::json")
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Webhook::Table)
+                    .add_column(ColumnDef::new(Webhook::LastEventId).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing webhooks already received the events ingested so far
+        manager
+            .get_connection()
+            .execute_unprepared("UPDATE webhooks SET last_event_id = (SELECT max(id) FROM events)")
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Webhook::Table)
+                    .drop_column(Webhook::LastEventId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Webhook {
+    #[sea_orm(iden = "webhooks")]
+    Table,
+    LastEventId,
+}
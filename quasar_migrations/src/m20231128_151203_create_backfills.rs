@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Backfills::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Backfills::Name)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Backfills::Position)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(Backfills::EndPosition)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Values of the events ingested so far were decoded with a converter
+        // losing addresses, 128 bits integers and map keys, they are decoded
+        // again from their XDR
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "INSERT INTO backfills (name, end_position) \
+                 SELECT 'event_values', coalesce(max(id), 0) FROM events",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Backfills::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Backfills {
+    Table,
+    Name,
+    Position,
+    EndPosition,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Rows ingested before the close time was stored would never match a
+        // `closedAt` filter, they take it from their ledger. Ledgers ingested
+        // before it was stored have no close time either, their rows are
+        // filled by the `ledger_close_metadata` backfill once it is read from
+        // the node
+        db.execute_unprepared(
+            "UPDATE transactions SET closed_at = ledgers.closed_at \
+             FROM ledgers \
+             WHERE transactions.closed_at IS NULL \
+             AND ledgers.sequence = transactions.ledger_sequence",
+        )
+        .await?;
+
+        for table in ["operations", "events"] {
+            db.execute_unprepared(&format!(
+                "UPDATE {table} SET closed_at = transactions.closed_at \
+                 FROM transactions \
+                 WHERE {table}.closed_at IS NULL \
+                 AND transactions.id = {table}.transaction_id"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // The close times are correct data, the backfill is deliberately not
+        // undone
+        Ok(())
+    }
+}
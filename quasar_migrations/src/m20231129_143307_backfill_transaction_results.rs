@@ -0,0 +1,33 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Transactions ingested before their result was stored are completed
+        // from the transaction history of the node, over the ledgers holding
+        // them
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "INSERT INTO backfills (name, position, end_position) \
+                 SELECT 'transaction_results', \
+                 coalesce(min(ledger_sequence) - 1, 0), coalesce(max(ledger_sequence), 0) \
+                 FROM transactions WHERE successful IS NULL",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM backfills WHERE name = 'transaction_results'")
+            .await?;
+
+        Ok(())
+    }
+}
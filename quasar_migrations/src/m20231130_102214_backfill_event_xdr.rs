@@ -0,0 +1,34 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Events ingested before their XDR was stored are completed from the
+        // transaction history of the node, over the ledgers holding them
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "INSERT INTO backfills (name, position, end_position) \
+                 SELECT 'event_xdr', \
+                 coalesce(min(transactions.ledger_sequence) - 1, 0), \
+                 coalesce(max(transactions.ledger_sequence), 0) \
+                 FROM events JOIN transactions ON transactions.id = events.transaction_id \
+                 WHERE events.raw_value IS NULL",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM backfills WHERE name = 'event_xdr'")
+            .await?;
+
+        Ok(())
+    }
+}
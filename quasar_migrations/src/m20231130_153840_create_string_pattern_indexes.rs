@@ -0,0 +1,100 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Columns searched with string filters
+const COLUMNS: [(&str, &str); 13] = [
+    ("ledgers", "hash"),
+    ("accounts", "id"),
+    ("accounts", "home_domain"),
+    ("accounts", "inflation_destination"),
+    ("contracts", "address"),
+    ("contracts", "type"),
+    ("events", "contract_id"),
+    ("events", "topic"),
+    ("events", "type"),
+    ("transactions", "id"),
+    ("transactions", "account_id"),
+    ("operations", "type"),
+    ("upgrades", "type"),
+];
+
+/// Columns added to the string filters after their first indexes, which need
+/// an index for exact and case-insensitive matches as well
+const NEW_COLUMNS: [(&str, &str); 3] = [
+    ("accounts", "home_domain"),
+    ("accounts", "inflation_destination"),
+    ("transactions", "account_id"),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared("CREATE EXTENSION IF NOT EXISTS pg_trgm")
+            .await?;
+
+        for (table, column) in NEW_COLUMNS {
+            db.execute_unprepared(&format!(
+                "CREATE INDEX IF NOT EXISTS idx_{table}_{column} ON {table} ({column})"
+            ))
+            .await?;
+            db.execute_unprepared(&format!(
+                "CREATE INDEX IF NOT EXISTS idx_{table}_{column}_lower_pattern \
+                 ON {table} (lower({column}) text_pattern_ops)"
+            ))
+            .await?;
+        }
+
+        for (table, column) in COLUMNS {
+            // Case-sensitive prefix searches compare the value itself with
+            // `LIKE`, which needs the pattern operator class
+            db.execute_unprepared(&format!(
+                "CREATE INDEX IF NOT EXISTS idx_{table}_{column}_pattern \
+                 ON {table} ({column} text_pattern_ops)"
+            ))
+            .await?;
+
+            // `contains` searches match anywhere in the value, only trigram
+            // indexes can serve them
+            db.execute_unprepared(&format!(
+                "CREATE INDEX IF NOT EXISTS idx_{table}_{column}_trgm \
+                 ON {table} USING GIN ({column} gin_trgm_ops)"
+            ))
+            .await?;
+            db.execute_unprepared(&format!(
+                "CREATE INDEX IF NOT EXISTS idx_{table}_{column}_lower_trgm \
+                 ON {table} USING GIN (lower({column}) gin_trgm_ops)"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (table, column) in COLUMNS {
+            for suffix in ["pattern", "trgm", "lower_trgm"] {
+                db.execute_unprepared(&format!(
+                    "DROP INDEX IF EXISTS idx_{table}_{column}_{suffix}"
+                ))
+                .await?;
+            }
+        }
+
+        for (table, column) in NEW_COLUMNS {
+            for suffix in ["", "_lower_pattern"] {
+                db.execute_unprepared(&format!(
+                    "DROP INDEX IF EXISTS idx_{table}_{column}{suffix}"
+                ))
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
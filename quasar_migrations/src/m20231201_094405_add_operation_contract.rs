@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Operation::Table)
+                    .add_column(ColumnDef::new(Operation::ContractId).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_operations_contract_id_id")
+                    .table(Operation::Table)
+                    .col(Operation::ContractId)
+                    .col(Operation::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Contracts called by the operations ingested so far are read from
+        // the transaction history of the node, over the ledgers holding them
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "INSERT INTO backfills (name, position, end_position) \
+                 SELECT 'operation_contracts', \
+                 coalesce(min(transactions.ledger_sequence) - 1, 0), \
+                 coalesce(max(transactions.ledger_sequence), 0) \
+                 FROM operations JOIN transactions ON transactions.id = operations.transaction_id \
+                 WHERE operations.type = 'InvokeHostFunction'",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM backfills WHERE name = 'operation_contracts'")
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Operation::Table)
+                    .drop_column(Operation::ContractId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Operation {
+    #[sea_orm(iden = "operations")]
+    Table,
+    Id,
+    ContractId,
+}
@@ -0,0 +1,31 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Accounts, account data and contracts created before the first
+        // ingested ledger are only known to the node, they are loaded from its
+        // state in a single step
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "INSERT INTO backfills (name, position, end_position) \
+                 VALUES ('node_state', 0, 1)",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM backfills WHERE name = 'node_state'")
+            .await?;
+
+        Ok(())
+    }
+}
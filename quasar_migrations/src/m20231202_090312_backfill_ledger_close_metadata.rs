@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const COUNTS: [&str; 4] = [
+    "transaction_count",
+    "successful_transaction_count",
+    "failed_transaction_count",
+    "operation_count",
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Counts of ledgers whose transaction history is gone are unknown
+        // rather than zero
+        for column in COUNTS {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE ledgers ALTER COLUMN {column} DROP NOT NULL, \
+                 ALTER COLUMN {column} DROP DEFAULT"
+            ))
+            .await?;
+        }
+
+        // Ledgers ingested before the close metadata was stored have no close
+        // time, their close time, transaction set hash and counts are read
+        // again from the node
+        db.execute_unprepared(&format!(
+            "UPDATE ledgers SET {} WHERE closed_at IS NULL",
+            COUNTS.map(|column| format!("{column} = NULL")).join(", ")
+        ))
+        .await?;
+        db.execute_unprepared(
+            "INSERT INTO backfills (name, position, end_position) \
+             SELECT 'ledger_close_metadata', \
+             coalesce(min(sequence) - 1, 0), coalesce(max(sequence), 0) \
+             FROM ledgers WHERE closed_at IS NULL",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared("DELETE FROM backfills WHERE name = 'ledger_close_metadata'")
+            .await?;
+
+        for column in COUNTS {
+            db.execute_unprepared(&format!(
+                "UPDATE ledgers SET {column} = 0 WHERE {column} IS NULL"
+            ))
+            .await?;
+            db.execute_unprepared(&format!(
+                "ALTER TABLE ledgers ALTER COLUMN {column} SET NOT NULL, \
+                 ALTER COLUMN {column} SET DEFAULT 0"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,576 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextValidation},
+    Data, ServerError, ValidationResult,
+};
+use axum::{
+    extract::State,
+    http::{header::RETRY_AFTER, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use clap::Subcommand;
+use log::error;
+use prometheus::{IntCounterVec, Opts, Registry};
+use quasar_entities::api_key;
+use rand::RngCore;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, Set, Statement,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sqlx::postgres::PgListener;
+
+use crate::{configuration::Api, databases::QuasarDatabase};
+
+/// Header carrying the API key, `Authorization: Bearer` is accepted as well
+const API_KEY_HEADER: &str = "X-API-Key";
+/// Query parameter carrying the API key, for WebSocket and EventSource
+/// clients which cannot set headers
+const API_KEY_PARAMETER: &str = "api_key";
+/// Field of the GraphQL WebSocket `connection_init` payload carrying the API key
+const API_KEY_PAYLOAD_FIELD: &str = "apiKey";
+/// Prefix of the generated keys, making them easy to spot in secret scanners
+const API_KEY_PREFIX: &str = "qsr_";
+/// Period over which the rate limits and complexity budgets are counted
+const WINDOW: Duration = Duration::from_secs(60);
+/// Name of the clients without an API key in the API metrics
+const ANONYMOUS: &str = "anonymous";
+/// How long a looked up key is trusted before it is looked up again, bounding
+/// how long a revoked key stays accepted when its notification is missed
+const KEY_CACHE_TTL: Duration = Duration::from_secs(30);
+/// Channel notified with the hash of every revoked key
+const REVOKED_CHANNEL: &str = "api_key_revoked";
+
+#[derive(Subcommand, Debug)]
+pub(super) enum ApiKeyCommand {
+    /// Create a key for a client and print it, it cannot be shown again
+    Create {
+        /// Name of the client
+        name: String,
+        /// Requests allowed per minute
+        #[arg(long, default_value_t = 60)]
+        rate_limit: i32,
+        /// Complexity of the GraphQL queries allowed per minute
+        #[arg(long)]
+        complexity_budget: Option<i32>,
+    },
+    /// List the keys and their limits
+    List,
+    /// Revoke the key of a client
+    Revoke {
+        /// Name of the client
+        name: String,
+    },
+}
+
+/// Requests and complexity spent by a key in the current window
+struct Window {
+    started_at: Instant,
+    requests: u32,
+    complexity: u64,
+}
+
+impl Window {
+    fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            requests: 0,
+            complexity: 0,
+        }
+    }
+
+    fn retry_after(&self) -> Duration {
+        WINDOW.saturating_sub(self.started_at.elapsed())
+    }
+}
+
+/// Key looked up in the database, `None` when no key has the hash
+struct CachedKey {
+    api_key: Option<api_key::Model>,
+    cached_at: Instant,
+}
+
+/// Where the routes accept the API key from, keys in query parameters end up
+/// in access logs and are only accepted from clients which cannot set headers
+#[derive(Clone, Copy)]
+pub(super) enum KeySource {
+    /// `X-API-Key` or `Authorization: Bearer` header
+    Headers,
+    /// Header or `api_key` query parameter, for EventSource clients
+    HeadersOrQuery,
+    /// Header, `api_key` query parameter or the `connection_init` payload of
+    /// the GraphQL WebSocket protocol, upgrades without a key are left to the
+    /// connection to authenticate
+    WebSocket,
+}
+
+/// Reason an API key was not accepted
+pub(super) enum Refusal {
+    Missing,
+    Invalid,
+    RateLimited(Duration),
+    Internal,
+}
+
+impl Refusal {
+    fn message(&self) -> &'static str {
+        match self {
+            Self::Missing => "API key required",
+            Self::Invalid => "invalid API key",
+            Self::RateLimited(_) => "rate limit exceeded",
+            Self::Internal => "internal error",
+        }
+    }
+}
+
+impl IntoResponse for Refusal {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Self::Missing | Self::Invalid => StatusCode::UNAUTHORIZED,
+            Self::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let mut response = (status, Json(json!({ "error": self.message() }))).into_response();
+
+        if let Self::RateLimited(retry_after) = self {
+            response.headers_mut().insert(
+                RETRY_AFTER,
+                retry_after.as_secs().max(1).to_string().parse().unwrap(),
+            );
+        }
+
+        response
+    }
+}
+
+/// Authenticates the API clients and enforces the limits of their keys
+#[derive(Clone)]
+pub(super) struct ApiKeys {
+    database: DatabaseConnection,
+    /// Reject requests without a key instead of serving them anonymously
+    required: bool,
+    /// Limits shared by the clients without a key, as a key which cannot be
+    /// presented
+    anonymous: api_key::Model,
+    /// Keys looked up recently, by hash
+    keys: Arc<Mutex<HashMap<String, CachedKey>>>,
+    windows: Arc<Mutex<HashMap<i32, Window>>>,
+    requests: IntCounterVec,
+    complexity: IntCounterVec,
+}
+
+impl ApiKeys {
+    pub fn new(database: &QuasarDatabase, api: &Api, registry: &Registry) -> Self {
+        let requests = IntCounterVec::new(
+            Opts::new("api_requests", "Number of API requests per key"),
+            &["key", "status"],
+        )
+        .unwrap();
+        let complexity = IntCounterVec::new(
+            Opts::new(
+                "api_query_complexity",
+                "Complexity of the GraphQL queries per key",
+            ),
+            &["key"],
+        )
+        .unwrap();
+        registry
+            .register(Box::new(requests.clone()))
+            .expect("Failed to register counter");
+        registry
+            .register(Box::new(complexity.clone()))
+            .expect("Failed to register counter");
+
+        let api_keys = Self {
+            database: database.as_inner().clone(),
+            required: api.require_api_key,
+            // Serial ids of the stored keys start at 1
+            anonymous: api_key::Model {
+                id: 0,
+                name: ANONYMOUS.to_string(),
+                key_hash: String::new(),
+                rate_limit: api.anonymous_rate_limit,
+                complexity_budget: Some(api.anonymous_complexity_budget),
+                created_at: Utc::now().into(),
+            },
+            keys: Arc::new(Mutex::new(HashMap::new())),
+            windows: Arc::new(Mutex::new(HashMap::new())),
+            requests,
+            complexity,
+        };
+
+        tokio::spawn(api_keys.clone().prune());
+        tokio::spawn(api_keys.clone().forget_revoked_keys());
+
+        api_keys
+    }
+
+    async fn find(&self, key: &str) -> Result<Option<api_key::Model>, DbErr> {
+        let key_hash = hash_key(key);
+
+        if let Some(cached) = self
+            .keys
+            .lock()
+            .expect("API key cache lock poisoned")
+            .get(&key_hash)
+        {
+            if cached.cached_at.elapsed() < KEY_CACHE_TTL {
+                return Ok(cached.api_key.clone());
+            }
+        }
+
+        let api_key = api_key::Entity::find()
+            .filter(api_key::Column::KeyHash.eq(&key_hash))
+            .one(&self.database)
+            .await?;
+
+        self.keys
+            .lock()
+            .expect("API key cache lock poisoned")
+            .insert(
+                key_hash,
+                CachedKey {
+                    api_key: api_key.clone(),
+                    cached_at: Instant::now(),
+                },
+            );
+        Ok(api_key)
+    }
+
+    /// Drops the expired keys and windows, including the ones of keys revoked
+    /// or never presented again
+    async fn prune(self) {
+        let mut interval = tokio::time::interval(WINDOW);
+        loop {
+            interval.tick().await;
+
+            self.keys
+                .lock()
+                .expect("API key cache lock poisoned")
+                .retain(|_, cached| cached.cached_at.elapsed() < KEY_CACHE_TTL);
+            self.windows
+                .lock()
+                .expect("Rate limiter lock poisoned")
+                .retain(|_, window| window.started_at.elapsed() < WINDOW);
+        }
+    }
+
+    /// Forgets the keys revoked from the command line as soon as they are
+    /// notified, the cache expiry covers the notifications missed
+    async fn forget_revoked_keys(self) {
+        let pool = self.database.get_postgres_connection_pool();
+        let mut listener = match PgListener::connect_with(pool).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                error!("Failed to listen to API key revocations: {}", err);
+                return;
+            }
+        };
+        if let Err(err) = listener.listen(REVOKED_CHANNEL).await {
+            error!("Failed to listen to API key revocations: {}", err);
+            return;
+        }
+
+        loop {
+            match listener.recv().await {
+                Ok(notification) => {
+                    self.keys
+                        .lock()
+                        .expect("API key cache lock poisoned")
+                        .remove(notification.payload());
+                }
+                Err(err) => {
+                    error!("Stopped listening to API key revocations: {}", err);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn with_window<R>(&self, api_key: &api_key::Model, f: impl FnOnce(&mut Window) -> R) -> R {
+        let mut windows = self.windows.lock().expect("Rate limiter lock poisoned");
+        let window = windows.entry(api_key.id).or_insert_with(Window::new);
+        if window.started_at.elapsed() >= WINDOW {
+            *window = Window::new();
+        }
+        f(window)
+    }
+
+    /// Looks up the presented key and counts a request against its rate
+    /// limit, requests without a key are served under the anonymous limits
+    /// unless keys are required
+    async fn admit(&self, key: Option<String>) -> Result<api_key::Model, Refusal> {
+        let api_key = match key {
+            Some(key) => match self.find(&key).await {
+                Ok(Some(api_key)) => api_key,
+                Ok(None) => return Err(Refusal::Invalid),
+                Err(err) => {
+                    error!("Failed to look up API key: {}", err);
+                    return Err(Refusal::Internal);
+                }
+            },
+            None if self.required => return Err(Refusal::Missing),
+            None => self.anonymous.clone(),
+        };
+
+        if let Err(retry_after) = self.acquire(&api_key) {
+            self.requests
+                .with_label_values(&[&api_key.name, "rate_limited"])
+                .inc();
+            return Err(Refusal::RateLimited(retry_after));
+        }
+
+        self.requests
+            .with_label_values(&[&api_key.name, "accepted"])
+            .inc();
+        Ok(api_key)
+    }
+
+    /// Data of a GraphQL WebSocket connection, holding the key authenticated
+    /// on the upgrade request or else the key of the `connection_init` payload
+    pub async fn connection_data(
+        &self,
+        api_key: Option<api_key::Model>,
+        payload: Value,
+    ) -> async_graphql::Result<Data> {
+        let api_key = match api_key {
+            Some(api_key) => api_key,
+            None => {
+                let key = payload
+                    .get(API_KEY_PAYLOAD_FIELD)
+                    .and_then(Value::as_str)
+                    .map(ToString::to_string);
+                self.admit(key)
+                    .await
+                    .map_err(|refusal| async_graphql::Error::new(refusal.message()))?
+            }
+        };
+
+        let mut data = Data::default();
+        data.insert(api_key);
+        Ok(data)
+    }
+
+    /// Counts a request against the rate limit of the key, returns how long
+    /// to wait when the limit is reached
+    fn acquire(&self, api_key: &api_key::Model) -> Result<(), Duration> {
+        self.with_window(api_key, |window| {
+            if i64::from(window.requests) >= i64::from(api_key.rate_limit) {
+                return Err(window.retry_after());
+            }
+            window.requests += 1;
+            Ok(())
+        })
+    }
+
+    /// Spends the complexity of a query from the budget of the key, returns
+    /// how long to wait when the budget is exhausted
+    fn spend_complexity(
+        &self,
+        api_key: &api_key::Model,
+        complexity: usize,
+    ) -> Result<(), Duration> {
+        self.complexity
+            .with_label_values(&[&api_key.name])
+            .inc_by(complexity as u64);
+
+        let budget = match api_key.complexity_budget {
+            Some(budget) => budget.max(0) as u64,
+            None => return Ok(()),
+        };
+
+        self.with_window(api_key, |window| {
+            if window.complexity + complexity as u64 > budget {
+                return Err(window.retry_after());
+            }
+            window.complexity += complexity as u64;
+            Ok(())
+        })
+    }
+}
+
+fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn generate_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", API_KEY_PREFIX, hex::encode(bytes))
+}
+
+fn presented_key<B>(request: &Request<B>, source: KeySource) -> Option<String> {
+    let headers = request.headers();
+
+    if let Some(key) = headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        return Some(key.to_string());
+    }
+
+    if let Some(key) = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    {
+        return Some(key.to_string());
+    }
+
+    if matches!(source, KeySource::Headers) {
+        return None;
+    }
+
+    request.uri().query().and_then(|query| {
+        query.split('&').find_map(|parameter| {
+            parameter
+                .strip_prefix(API_KEY_PARAMETER)
+                .and_then(|value| value.strip_prefix('='))
+                .map(ToString::to_string)
+        })
+    })
+}
+
+/// Middleware authenticating the request by its API key and applying the rate
+/// limit of the key, the key is handed to the handlers as a request extension
+pub(super) async fn authenticate<B>(
+    State((api_keys, source)): State<(ApiKeys, KeySource)>,
+    mut request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let key = presented_key(&request, source);
+    if key.is_none() && matches!(source, KeySource::WebSocket) {
+        return next.run(request).await;
+    }
+
+    match api_keys.admit(key).await {
+        Ok(api_key) => {
+            request.extensions_mut().insert(api_key);
+            next.run(request).await
+        }
+        Err(refusal) => refusal.into_response(),
+    }
+}
+
+/// GraphQL extension spending the complexity of every query from the budget
+/// of the API key of the request
+pub(super) struct ComplexityBudget(pub ApiKeys);
+
+impl ExtensionFactory for ComplexityBudget {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ComplexityBudgetExtension(self.0.clone()))
+    }
+}
+
+struct ComplexityBudgetExtension(ApiKeys);
+
+#[async_graphql::async_trait::async_trait]
+impl Extension for ComplexityBudgetExtension {
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> Result<ValidationResult, Vec<ServerError>> {
+        let result = next.run(ctx).await?;
+
+        if let Some(api_key) = ctx.data_opt::<api_key::Model>() {
+            if let Err(retry_after) = self.0.spend_complexity(api_key, result.complexity) {
+                return Err(vec![ServerError::new(
+                    format!(
+                        "Complexity budget of the API key exhausted, retry in {} seconds",
+                        retry_after.as_secs().max(1)
+                    ),
+                    None,
+                )]);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+pub(super) async fn run_api_key_command(database: &QuasarDatabase, command: ApiKeyCommand) {
+    let database = database.as_inner();
+
+    match command {
+        ApiKeyCommand::Create {
+            name,
+            rate_limit,
+            complexity_budget,
+        } => {
+            let key = generate_key();
+            let api_key = api_key::ActiveModel {
+                name: Set(name),
+                key_hash: Set(hash_key(&key)),
+                rate_limit: Set(rate_limit),
+                complexity_budget: Set(complexity_budget),
+                ..Default::default()
+            };
+            let api_key = api_key
+                .insert(database)
+                .await
+                .expect("Failed to create API key");
+
+            println!("Created API key for {}: {}", api_key.name, key);
+        }
+        ApiKeyCommand::List => {
+            let api_keys = api_key::Entity::find()
+                .order_by_asc(api_key::Column::Name)
+                .all(database)
+                .await
+                .expect("Failed to list API keys");
+
+            for api_key in api_keys {
+                let budget = match api_key.complexity_budget {
+                    Some(budget) => budget.to_string(),
+                    None => "unlimited".to_string(),
+                };
+                println!(
+                    "{}\t{} requests/min\tcomplexity budget {}/min\tcreated {}",
+                    api_key.name, api_key.rate_limit, budget, api_key.created_at
+                );
+            }
+        }
+        ApiKeyCommand::Revoke { name } => {
+            let api_keys = api_key::Entity::find()
+                .filter(api_key::Column::Name.eq(&name))
+                .all(database)
+                .await
+                .expect("Failed to revoke API key");
+
+            if api_keys.is_empty() {
+                println!("No API key for {}", name);
+                return;
+            }
+
+            api_key::Entity::delete_many()
+                .filter(api_key::Column::Name.eq(&name))
+                .exec(database)
+                .await
+                .expect("Failed to revoke API key");
+
+            // Running servers drop the key from their cache
+            for api_key in api_keys {
+                database
+                    .execute(Statement::from_sql_and_values(
+                        DatabaseBackend::Postgres,
+                        "SELECT pg_notify($1, $2)",
+                        [REVOKED_CHANNEL.into(), api_key.key_hash.into()],
+                    ))
+                    .await
+                    .expect("Failed to notify the API key revocation");
+            }
+
+            println!("Revoked API key for {}", name);
+        }
+    }
+}
@@ -13,6 +13,20 @@ pub struct Ingestion {
 #[derive(Debug, Deserialize, PartialEq, Eq)]
 pub struct Metrics {
     pub database_polling_interval: u64,
+    /// Address of `/metrics`, apart from the API as the metrics are labelled
+    /// with the names of the API clients
+    #[serde(default = "default_metrics_host")]
+    pub host: Ipv4Addr,
+    #[serde(default = "default_metrics_port")]
+    pub port: u16,
+}
+
+fn default_metrics_host() -> Ipv4Addr {
+    Ipv4Addr::LOCALHOST
+}
+
+fn default_metrics_port() -> u16 {
+    9100
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -22,6 +36,70 @@ pub struct Api {
 
     pub depth_limit: usize,
     pub complexity_limit: usize,
+    /// Reject requests that do not present a valid API key
+    #[serde(default)]
+    pub require_api_key: bool,
+    /// Requests per minute shared by the clients without an API key
+    #[serde(default = "default_anonymous_rate_limit")]
+    pub anonymous_rate_limit: i32,
+    /// Complexity of the GraphQL queries per minute shared by the clients
+    /// without an API key
+    #[serde(default = "default_anonymous_complexity_budget")]
+    pub anonymous_complexity_budget: i32,
+}
+
+fn default_anonymous_rate_limit() -> i32 {
+    60
+}
+
+fn default_anonymous_complexity_budget() -> i32 {
+    1000
+}
+
+/// Endpoint registered from the configuration file, events are delivered
+/// when they match every filter that is set
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    pub secret: String,
+    pub contract_id: Option<String>,
+    pub topic: Option<String>,
+    pub r#type: Option<String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct Webhooks {
+    /// Delivery attempts before an event is moved to the dead letters
+    pub max_attempts: u32,
+    /// Delay in milliseconds before the first retry, doubled on every retry
+    pub initial_backoff: u64,
+    /// Seconds an event is retried at most, the later events of its webhook
+    /// wait until it is delivered or moved to the dead letters
+    pub max_retry_duration: u64,
+    /// Request timeout in seconds
+    pub timeout: u64,
+    /// Requests in flight at once across all webhooks
+    pub max_concurrent_deliveries: usize,
+    /// Base64 encoded 32 byte key encrypting the webhook secrets stored in
+    /// the database, they are stored in plaintext without it
+    pub secret_key: Option<String>,
+
+    pub endpoints: Vec<WebhookEndpoint>,
+}
+
+impl Default for Webhooks {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: 500,
+            max_retry_duration: 300,
+            timeout: 10,
+            max_concurrent_deliveries: 16,
+            secret_key: None,
+            endpoints: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, PartialEq, Eq)]
@@ -32,6 +110,8 @@ pub struct Configuration {
     pub ingestion: Ingestion,
     pub api: Api,
     pub metrics: Metrics,
+    #[serde(default)]
+    pub webhooks: Webhooks,
 }
 
 pub(super) fn setup_configuration(args: Args) -> Configuration {
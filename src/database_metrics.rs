@@ -8,13 +8,15 @@ use tokio::time;
 
 use crate::databases::QuasarDatabase;
 
-const GAUGES: [&str; 6] = [
+const GAUGES: [&str; 8] = [
     "ledgers",
     "accounts",
+    "account_data",
     "contracts",
     "transactions",
     "operations",
     "events",
+    "upgrades",
 ];
 
 pub(super) fn start_database_metrics(database: QuasarDatabase, registry: Registry, interval: u64) {
@@ -60,10 +62,12 @@ async fn count_entities(db: &QuasarDatabase, gauges: &mut HashMap<String, IntGau
         let query = match gauge_name.as_str() {
             "ledgers" => Ledger::find().count(db),
             "accounts" => Account::find().count(db),
+            "account_data" => AccountData::find().count(db),
             "contracts" => Contract::find().count(db),
             "transactions" => Transaction::find().count(db),
             "operations" => Operation::find().count(db),
             "events" => Event::find().count(db),
+            "upgrades" => Upgrade::find().count(db),
             _ => panic!("Unknown gauge name"),
         };
 
@@ -0,0 +1,634 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use log::error;
+use quasar_entities::{account, account_signer, ledger, operation, transaction};
+use sea_orm::{
+    sea_query::{Expr, SimpleExpr},
+    ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, IntoSimpleExpr, Order,
+    QueryFilter, QueryOrder, QuerySelect, Select,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use stellar_xdr::curr::AccountFlags;
+use thiserror::Error;
+
+/// Records per page when the request does not set a limit, same as Horizon
+const DEFAULT_LIMIT: u64 = 10;
+const MAX_LIMIT: u64 = 200;
+const STROOPS_PER_LUMEN: u64 = 10_000_000;
+
+#[derive(Error, Debug)]
+enum HorizonError {
+    #[error("The resource at the url requested was not found.")]
+    NotFound,
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("Database error: {0}")]
+    Database(#[from] DbErr),
+}
+
+impl IntoResponse for HorizonError {
+    fn into_response(self) -> Response {
+        let (status, kind, title) = match &self {
+            Self::NotFound => (StatusCode::NOT_FOUND, "not_found", "Resource Missing"),
+            Self::BadRequest(_) => (StatusCode::BAD_REQUEST, "bad_request", "Bad Request"),
+            Self::Database(err) => {
+                error!("Failed to serve Horizon request: {}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "server_error",
+                    "Internal Server Error",
+                )
+            }
+        };
+
+        let detail = match &self {
+            Self::Database(_) => "An error occurred while processing this request.".to_string(),
+            _ => self.to_string(),
+        };
+
+        let problem = json!({
+            "type": format!("https://stellar.org/horizon-errors/{}", kind),
+            "title": title,
+            "status": status.as_u16(),
+            "detail": detail,
+        });
+
+        (status, Json(problem)).into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    cursor: Option<String>,
+    limit: Option<u64>,
+    order: Option<String>,
+}
+
+struct Page {
+    cursor: Option<i64>,
+    limit: u64,
+    ascending: bool,
+}
+
+impl TryFrom<PageQuery> for Page {
+    type Error = HorizonError;
+
+    fn try_from(query: PageQuery) -> Result<Self, Self::Error> {
+        let cursor = match query.cursor.as_deref() {
+            None | Some("") => None,
+            Some(cursor) => Some(cursor.parse().map_err(|_| {
+                HorizonError::BadRequest("cursor must be a paging token".to_string())
+            })?),
+        };
+
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(HorizonError::BadRequest(format!(
+                "limit must be between 1 and {}",
+                MAX_LIMIT
+            )));
+        }
+
+        let ascending = match query.order.as_deref() {
+            None | Some("asc") => true,
+            Some("desc") => false,
+            Some(_) => {
+                return Err(HorizonError::BadRequest(
+                    "order must be asc or desc".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            cursor,
+            limit,
+            ascending,
+        })
+    }
+}
+
+impl Page {
+    fn order(&self) -> Order {
+        if self.ascending {
+            Order::Asc
+        } else {
+            Order::Desc
+        }
+    }
+
+    fn href(&self, path: &str, cursor: Option<&str>, ascending: bool) -> String {
+        let order = if ascending { "asc" } else { "desc" };
+        format!(
+            "{}?cursor={}&limit={}&order={}",
+            path,
+            cursor.unwrap_or_default(),
+            self.limit,
+            order
+        )
+    }
+
+    /// Wraps the records in a HAL collection with links to the next and
+    /// previous pages
+    fn respond(&self, path: &str, records: Vec<Value>) -> Json<Value> {
+        let cursor = self.cursor.map(|cursor| cursor.to_string());
+        let first = records.first().and_then(paging_token).or(cursor.clone());
+        let last = records.last().and_then(paging_token).or(cursor.clone());
+
+        Json(json!({
+            "_links": {
+                "self": { "href": self.href(path, cursor.as_deref(), self.ascending) },
+                "next": { "href": self.href(path, last.as_deref(), self.ascending) },
+                "prev": { "href": self.href(path, first.as_deref(), !self.ascending) },
+            },
+            "_embedded": {
+                "records": records,
+            },
+        }))
+    }
+}
+
+fn paging_token(record: &Value) -> Option<String> {
+    record["paging_token"].as_str().map(ToString::to_string)
+}
+
+/// Horizon's total order id of a ledger
+fn ledger_toid(sequence: i32) -> i64 {
+    (sequence as i64) << 32
+}
+
+/// Horizon's total order id of a transaction
+fn transaction_toid(ledger_sequence: i32, application_order: i32) -> i64 {
+    ledger_toid(ledger_sequence) | ((application_order as i64) << 12)
+}
+
+fn parse_transaction_toid(toid: i64) -> (i32, i32) {
+    ((toid >> 32) as i32, ((toid >> 12) & 0xF_FFFF) as i32)
+}
+
+/// Horizon's total order id of an operation
+pub(crate) fn operation_toid(
+    ledger_sequence: i32,
+    transaction_order: i32,
+    application_order: i32,
+) -> i64 {
+    transaction_toid(ledger_sequence, transaction_order) | (application_order as i64 & 0xFFF)
+}
+
+pub(crate) fn parse_operation_toid(toid: i64) -> (i32, i32, i32) {
+    let (ledger_sequence, transaction_order) = parse_transaction_toid(toid);
+    (ledger_sequence, transaction_order, (toid & 0xFFF) as i32)
+}
+
+fn amount(stroops: i64) -> String {
+    let sign = if stroops < 0 { "-" } else { "" };
+    let stroops = stroops.unsigned_abs();
+    format!(
+        "{}{}.{:07}",
+        sign,
+        stroops / STROOPS_PER_LUMEN,
+        stroops % STROOPS_PER_LUMEN
+    )
+}
+
+/// Converts XDR names such as `CreateAccount` to Horizon's `create_account`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (index, character) in name.chars().enumerate() {
+        if character.is_uppercase() {
+            if index > 0 {
+                snake.push('_');
+            }
+            snake.extend(character.to_lowercase());
+        } else {
+            snake.push(character);
+        }
+    }
+    snake
+}
+
+fn signer_type(r#type: &str) -> &str {
+    match r#type {
+        "Ed25519" => "ed25519_public_key",
+        "PreAuthTx" => "preauth_tx",
+        "HashX" => "sha256_hash",
+        "Ed25519SignedPayload" => "ed25519_signed_payload",
+        other => other,
+    }
+}
+
+fn ledger_record(ledger: &ledger::Model) -> Value {
+    let path = format!("/ledgers/{}", ledger.sequence);
+
+    json!({
+        "_links": {
+            "self": { "href": path },
+            "transactions": {
+                "href": format!("{}/transactions{{?cursor,limit,order}}", path),
+                "templated": true,
+            },
+        },
+        "id": ledger.hash,
+        "paging_token": ledger_toid(ledger.sequence).to_string(),
+        "hash": ledger.hash,
+        "prev_hash": ledger.previous_ledger_hash,
+        "sequence": ledger.sequence,
+        "successful_transaction_count": ledger.successful_transaction_count,
+        "failed_transaction_count": ledger.failed_transaction_count,
+        "operation_count": ledger.operation_count,
+        "closed_at": ledger.closed_at,
+        "total_coins": amount(ledger.total_coins),
+        "fee_pool": amount(ledger.fee_pool),
+        "base_fee_in_stroops": ledger.base_fee,
+        "base_reserve_in_stroops": ledger.base_reserve,
+        "max_tx_set_size": ledger.max_tx_set_size,
+        "protocol_version": ledger.protocol_version,
+    })
+}
+
+fn transaction_record(transaction: &transaction::Model) -> Value {
+    let path = format!("/transactions/{}", transaction.id);
+
+    json!({
+        "_links": {
+            "self": { "href": path },
+            "account": { "href": format!("/accounts/{}", transaction.account_id) },
+            "ledger": { "href": format!("/ledgers/{}", transaction.ledger_sequence) },
+            "operations": {
+                "href": format!("{}/operations{{?cursor,limit,order}}", path),
+                "templated": true,
+            },
+        },
+        "id": transaction.id,
+        "paging_token": transaction_toid(
+            transaction.ledger_sequence,
+            transaction.application_order,
+        )
+        .to_string(),
+        "hash": transaction.id,
+        "ledger": transaction.ledger_sequence,
+        "created_at": transaction.closed_at,
+        "source_account": transaction.account_id,
+        "source_account_sequence": transaction.account_sequence.to_string(),
+        "successful": transaction.successful,
+        "fee_charged": transaction.fee_charged.map(|fee| fee.to_string()),
+        "operation_count": transaction.operation_count,
+    })
+}
+
+fn operation_record(operation: &operation::Model, transaction: &transaction::Model) -> Value {
+    let toid = operation_toid(
+        transaction.ledger_sequence,
+        transaction.application_order,
+        operation.application_order,
+    )
+    .to_string();
+
+    json!({
+        "_links": {
+            "transaction": { "href": format!("/transactions/{}", operation.transaction_id) },
+        },
+        "id": toid,
+        "paging_token": toid,
+        "transaction_hash": operation.transaction_id,
+        "type": snake_case(&operation.r#type),
+        "created_at": operation.closed_at,
+    })
+}
+
+fn account_record(account: &account::Model, signers: &[account_signer::Model]) -> Value {
+    let path = format!("/accounts/{}", account.id);
+    let has_flag = |flag: AccountFlags| account.flags & flag as i32 != 0;
+
+    let mut account_signers = vec![json!({
+        "key": account.id,
+        "weight": account.master_weight,
+        "type": "ed25519_public_key",
+    })];
+    account_signers.extend(signers.iter().map(|signer| {
+        json!({
+            "key": signer.signer,
+            "weight": signer.weight,
+            "type": signer_type(&signer.r#type),
+            "sponsor": signer.sponsor,
+        })
+    }));
+
+    json!({
+        "_links": {
+            "self": { "href": path },
+        },
+        "id": account.id,
+        "account_id": account.id,
+        "paging_token": account.id,
+        "sequence": account.sequence_number.to_string(),
+        "sequence_ledger": account.sequence_ledger,
+        "sequence_time": account.sequence_time.map(|time| time.to_string()),
+        "subentry_count": account.number_of_subentries,
+        "inflation_destination": account.inflation_destination,
+        "home_domain": account.home_domain,
+        "last_modified_ledger": account.last_modified,
+        "num_sponsoring": account.number_of_sponsoring,
+        "num_sponsored": account.number_of_sponsored,
+        "sponsor": account.sponsor,
+        "thresholds": {
+            "low_threshold": account.threshold_low,
+            "med_threshold": account.threshold_medium,
+            "high_threshold": account.threshold_high,
+        },
+        "flags": {
+            "auth_required": has_flag(AccountFlags::RequiredFlag),
+            "auth_revocable": has_flag(AccountFlags::RevocableFlag),
+            "auth_immutable": has_flag(AccountFlags::ImmutableFlag),
+            "auth_clawback_enabled": has_flag(AccountFlags::ClawbackEnabledFlag),
+        },
+        "balances": [{
+            "balance": amount(account.balance),
+            "buying_liabilities": amount(account.buying_liabilities.unwrap_or_default()),
+            "selling_liabilities": amount(account.selling_liabilities.unwrap_or_default()),
+            "asset_type": "native",
+        }],
+        "signers": account_signers,
+    })
+}
+
+/// Keyset condition on the transactions following the paging token
+fn after_transaction(page: &Page) -> Option<Condition> {
+    let (ledger_sequence, application_order) = parse_transaction_toid(page.cursor?);
+
+    let (ledger_after, order_after) = if page.ascending {
+        (
+            transaction::Column::LedgerSequence.gt(ledger_sequence),
+            transaction::Column::ApplicationOrder.gt(application_order),
+        )
+    } else {
+        (
+            transaction::Column::LedgerSequence.lt(ledger_sequence),
+            transaction::Column::ApplicationOrder.lt(application_order),
+        )
+    };
+
+    Some(
+        Condition::any().add(ledger_after).add(
+            Condition::all()
+                .add(transaction::Column::LedgerSequence.eq(ledger_sequence))
+                .add(order_after),
+        ),
+    )
+}
+
+/// Keyset condition on the operations following the paging token, operations
+/// are ordered by their ledger, transaction and application order
+fn after_operation(page: &Page) -> Result<Option<SimpleExpr>, HorizonError> {
+    let cursor = match page.cursor {
+        Some(cursor) if cursor < 0 => {
+            return Err(HorizonError::BadRequest(
+                "cursor must be a paging token".to_string(),
+            ))
+        }
+        Some(cursor) => cursor,
+        None => return Ok(None),
+    };
+    let (ledger_sequence, transaction_order, application_order) = parse_operation_toid(cursor);
+
+    let position = Expr::tuple([
+        transaction::Column::LedgerSequence.into_simple_expr(),
+        transaction::Column::ApplicationOrder.into_simple_expr(),
+        operation::Column::ApplicationOrder.into_simple_expr(),
+    ]);
+    let cursor = Expr::tuple([
+        ledger_sequence.into(),
+        transaction_order.into(),
+        application_order.into(),
+    ]);
+
+    Ok(Some(if page.ascending {
+        position.gt(cursor)
+    } else {
+        position.lt(cursor)
+    }))
+}
+
+/// Operations with their transaction, in Horizon's order
+async fn operation_records(
+    database: &DatabaseConnection,
+    select: Select<operation::Entity>,
+    page: &Page,
+) -> Result<Vec<Value>, HorizonError> {
+    let mut select = select.find_also_related(transaction::Entity);
+
+    if let Some(condition) = after_operation(page)? {
+        select = select.filter(condition);
+    }
+
+    Ok(select
+        .order_by(transaction::Column::LedgerSequence, page.order())
+        .order_by(transaction::Column::ApplicationOrder, page.order())
+        .order_by(operation::Column::ApplicationOrder, page.order())
+        .limit(page.limit)
+        .all(database)
+        .await?
+        .iter()
+        .filter_map(|(operation, transaction)| {
+            transaction
+                .as_ref()
+                .map(|transaction| operation_record(operation, transaction))
+        })
+        .collect())
+}
+
+async fn ledgers(
+    State(database): State<DatabaseConnection>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<Value>, HorizonError> {
+    let page = Page::try_from(query)?;
+    let mut select = ledger::Entity::find();
+
+    if let Some(cursor) = page.cursor {
+        let sequence = (cursor >> 32) as i32;
+        select = if page.ascending {
+            select.filter(ledger::Column::Sequence.gt(sequence))
+        } else {
+            select.filter(ledger::Column::Sequence.lt(sequence))
+        };
+    }
+
+    let records = select
+        .order_by(ledger::Column::Sequence, page.order())
+        .limit(page.limit)
+        .all(&database)
+        .await?
+        .iter()
+        .map(ledger_record)
+        .collect();
+
+    Ok(page.respond("/ledgers", records))
+}
+
+async fn ledger(
+    State(database): State<DatabaseConnection>,
+    Path(sequence): Path<i32>,
+) -> Result<Json<Value>, HorizonError> {
+    let ledger = ledger::Entity::find()
+        .filter(ledger::Column::Sequence.eq(sequence))
+        .one(&database)
+        .await?
+        .ok_or(HorizonError::NotFound)?;
+
+    Ok(Json(ledger_record(&ledger)))
+}
+
+async fn ledger_transactions(
+    State(database): State<DatabaseConnection>,
+    Path(sequence): Path<i32>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<Value>, HorizonError> {
+    let page = Page::try_from(query)?;
+    let mut select =
+        transaction::Entity::find().filter(transaction::Column::LedgerSequence.eq(sequence));
+
+    if let Some(condition) = after_transaction(&page) {
+        select = select.filter(condition);
+    }
+
+    let records = select
+        .order_by(transaction::Column::LedgerSequence, page.order())
+        .order_by(transaction::Column::ApplicationOrder, page.order())
+        .limit(page.limit)
+        .all(&database)
+        .await?
+        .iter()
+        .map(transaction_record)
+        .collect();
+
+    Ok(page.respond(&format!("/ledgers/{}/transactions", sequence), records))
+}
+
+async fn account(
+    State(database): State<DatabaseConnection>,
+    Path(account_id): Path<String>,
+) -> Result<Json<Value>, HorizonError> {
+    let account = account::Entity::find_by_id(account_id.clone())
+        .filter(account::Column::DeletedInLedger.is_null())
+        .one(&database)
+        .await?
+        .ok_or(HorizonError::NotFound)?;
+
+    let signers = account_signer::Entity::find()
+        .filter(account_signer::Column::AccountId.eq(account_id))
+        .order_by_asc(account_signer::Column::Signer)
+        .all(&database)
+        .await?;
+
+    Ok(Json(account_record(&account, &signers)))
+}
+
+async fn transaction(
+    State(database): State<DatabaseConnection>,
+    Path(hash): Path<String>,
+) -> Result<Json<Value>, HorizonError> {
+    let transaction = transaction::Entity::find_by_id(hash)
+        .one(&database)
+        .await?
+        .ok_or(HorizonError::NotFound)?;
+
+    Ok(Json(transaction_record(&transaction)))
+}
+
+async fn transaction_operations(
+    State(database): State<DatabaseConnection>,
+    Path(hash): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<Value>, HorizonError> {
+    let page = Page::try_from(query)?;
+    let select = operation::Entity::find().filter(operation::Column::TransactionId.eq(&hash));
+    let records = operation_records(&database, select, &page).await?;
+
+    Ok(page.respond(&format!("/transactions/{}/operations", hash), records))
+}
+
+async fn operations(
+    State(database): State<DatabaseConnection>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<Value>, HorizonError> {
+    let page = Page::try_from(query)?;
+    let records = operation_records(&database, operation::Entity::find(), &page).await?;
+
+    Ok(page.respond("/operations", records))
+}
+
+/// Horizon compatible REST endpoints
+pub(super) fn router(database: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/ledgers", get(ledgers))
+        .route("/ledgers/:sequence", get(ledger))
+        .route("/ledgers/:sequence/transactions", get(ledger_transactions))
+        .route("/accounts/:account_id", get(account))
+        .route("/transactions/:hash", get(transaction))
+        .route(
+            "/transactions/:hash/operations",
+            get(transaction_operations),
+        )
+        .route("/operations", get(operations))
+        .with_state(database)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transaction_toid_matches_horizon() {
+        assert_eq!(transaction_toid(1, 1), 4294971392);
+        assert_eq!(parse_transaction_toid(4294971392), (1, 1));
+    }
+
+    #[test]
+    fn transaction_toid_round_trips() {
+        for (ledger_sequence, application_order) in [(1, 1), (48_000_000, 1), (2, 0xF_FFFF)] {
+            let toid = transaction_toid(ledger_sequence, application_order);
+            assert_eq!(
+                parse_transaction_toid(toid),
+                (ledger_sequence, application_order)
+            );
+        }
+    }
+
+    #[test]
+    fn operation_toid_round_trips() {
+        assert_eq!(operation_toid(1, 1, 1), 4294971393);
+
+        for (ledger_sequence, transaction_order, application_order) in
+            [(1, 1, 1), (48_000_000, 250, 100), (2, 0xF_FFFF, 0xFFF)]
+        {
+            let toid = operation_toid(ledger_sequence, transaction_order, application_order);
+            assert_eq!(
+                parse_operation_toid(toid),
+                (ledger_sequence, transaction_order, application_order)
+            );
+        }
+    }
+
+    #[test]
+    fn amount_formats_stroops_as_lumens() {
+        assert_eq!(amount(0), "0.0000000");
+        assert_eq!(amount(1), "0.0000001");
+        assert_eq!(amount(100_000_000), "10.0000000");
+        assert_eq!(amount(-1), "-0.0000001");
+        assert_eq!(amount(-123_456_789), "-12.3456789");
+        assert_eq!(amount(i64::MIN), "-922337203685.4775808");
+    }
+
+    #[test]
+    fn snake_case_converts_xdr_names() {
+        assert_eq!(snake_case("CreateAccount"), "create_account");
+        assert_eq!(snake_case("InvokeHostFunction"), "invoke_host_function");
+        assert_eq!(snake_case("Payment"), "payment");
+        assert_eq!(snake_case(""), "");
+    }
+}
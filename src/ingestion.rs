@@ -7,15 +7,27 @@ use thiserror::Error;
 use crate::{
     configuration::Ingestion,
     databases::{NodeDatabase, QuasarDatabase},
-    ingestion::ledgers::{ingest_ledgers, new_ledgers_available, IngestionNeeded},
+    ingestion::{
+        backfills::run_backfills,
+        ledgers::{ingest_ledgers, new_ledgers_available, IngestionNeeded},
+        network_config::seed_network_config,
+    },
+    notifications::Notifier,
 };
 
+mod account_data;
 mod accounts;
+mod backfills;
 mod contracts;
 mod events;
+mod ledger_entry_changes;
 mod ledgers;
+mod network_config;
+mod node_state;
 mod operations;
+mod quorum_sets;
 mod transactions;
+mod upgrades;
 
 #[derive(Error, Debug)]
 pub enum IngestionError {
@@ -23,20 +35,26 @@ pub enum IngestionError {
     DbError(#[from] DbErr),
     #[error("Missing ledger sequence")]
     MissingLedgerSequence,
+    #[error("Missing account id")]
+    MissingAccountId,
     #[error("XDR decoding error: {0}")]
     XdrError(#[from] stellar_xdr::curr::Error),
     #[error("Account error: {0}")]
     AccountError(#[from] AccountError),
     #[error("Event error: {0}")]
     EventError(#[from] EventError),
+    #[error("Strkey decoding error: {0}")]
+    StrkeyError(#[from] stellar_strkey::DecodeError),
 }
 pub(super) struct IngestionMetrics {
     pub ledgers: IntCounter,
     pub accounts: IntCounter,
+    pub account_data: IntCounter,
     pub contracts: IntCounter,
     pub transactions: IntCounter,
     pub operations: IntCounter,
     pub events: IntCounter,
+    pub upgrades: IntCounter,
 }
 
 pub(super) async fn ingest(
@@ -44,9 +62,20 @@ pub(super) async fn ingest(
     quasar_database: QuasarDatabase,
     ingestion: Ingestion,
     metrics: Registry,
+    notifier: Notifier,
 ) {
     let ingestion_metrics = setup_ingestion_metrics(&metrics);
 
+    if let Err(error) = seed_network_config(&node_database, &quasar_database).await {
+        error!("Error while seeding the network config: {}", error);
+    }
+
+    // Rewrite the rows ingested before changes of the ingestion
+    tokio::spawn(run_backfills(
+        node_database.clone(),
+        quasar_database.clone(),
+    ));
+
     loop {
         sleep(&ingestion).await;
 
@@ -62,6 +91,7 @@ pub(super) async fn ingest(
                     &quasar_database,
                     last_ingested_ledger_sequence,
                     &ingestion_metrics,
+                    &notifier,
                 )
                 .await;
 
@@ -85,17 +115,21 @@ fn setup_ingestion_metrics(metrics: &Registry) -> IngestionMetrics {
     let ledgers = create_ingestion_counter(metrics, "ledgers");
     let contracts = create_ingestion_counter(metrics, "contracts");
     let accounts = create_ingestion_counter(metrics, "accounts");
+    let account_data = create_ingestion_counter(metrics, "account_data");
     let transactions = create_ingestion_counter(metrics, "transactions");
     let operations = create_ingestion_counter(metrics, "operations");
     let events = create_ingestion_counter(metrics, "events");
+    let upgrades = create_ingestion_counter(metrics, "upgrades");
 
     IngestionMetrics {
         ledgers,
         contracts,
         accounts,
+        account_data,
         transactions,
         operations,
         events,
+        upgrades,
     }
 }
 
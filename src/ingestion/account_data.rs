@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use migration::OnConflict;
+use quasar_entities::{
+    account::{account_id_to_string, AccountError},
+    account_data,
+};
+use sea_orm::{ColumnTrait, Condition, DatabaseTransaction, EntityTrait, QueryFilter};
+use stellar_xdr::curr::LedgerKeyData;
+
+use super::{ledger_entry_changes::BATCH_SIZE, IngestionError};
+
+/// Account id and name of a data entry
+pub(super) fn data_key(key: &LedgerKeyData) -> Result<(String, String), IngestionError> {
+    let name = String::from_utf8(key.data_name.0.to_vec()).map_err(AccountError::from)?;
+    Ok((account_id_to_string(&key.account_id), name))
+}
+
+/// Writes the latest state of the data entries changed by a ledger, entries
+/// without state were removed
+pub(super) async fn write_account_data(
+    db: &DatabaseTransaction,
+    entries: HashMap<(String, String), Option<account_data::ActiveModel>>,
+) -> Result<(), IngestionError> {
+    let mut upserts = Vec::new();
+    let mut removed = Vec::new();
+    for (key, entry) in entries {
+        match entry {
+            Some(entry) => upserts.push(entry),
+            None => removed.push(key),
+        }
+    }
+
+    for chunk in upserts.chunks(BATCH_SIZE) {
+        account_data::Entity::insert_many(chunk.to_vec())
+            .on_conflict(
+                OnConflict::columns([account_data::Column::AccountId, account_data::Column::Name])
+                    .update_columns([
+                        account_data::Column::Value,
+                        account_data::Column::RawValue,
+                        account_data::Column::LastModified,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+    }
+
+    for chunk in removed.chunks(BATCH_SIZE) {
+        let mut condition = Condition::any();
+        for (account_id, name) in chunk {
+            condition = condition.add(
+                Condition::all()
+                    .add(account_data::Column::AccountId.eq(account_id))
+                    .add(account_data::Column::Name.eq(name)),
+            );
+        }
+
+        account_data::Entity::delete_many()
+            .filter(condition)
+            .exec(db)
+            .await?;
+    }
+
+    Ok(())
+}
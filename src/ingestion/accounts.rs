@@ -1,61 +1,165 @@
-use crate::databases::{NodeDatabase, QuasarDatabase};
-
-use super::{IngestionError, IngestionMetrics};
-use log::info;
-use migration::OnConflict;
-use quasar_entities::account;
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
-use stellar_node_entities::{accounts, prelude::Accounts};
-
-pub(super) async fn ingest_accounts(
-    node_database: &NodeDatabase,
-    quasar_database: &QuasarDatabase,
+use std::collections::HashMap;
+
+use crate::databases::QuasarDatabase;
+
+use super::{ledger_entry_changes::BATCH_SIZE, IngestionError};
+use migration::{Expr, OnConflict};
+use quasar_entities::{account, account_history, account_signer};
+use sea_orm::{ColumnTrait, DatabaseTransaction, EntityTrait, QueryFilter, Set};
+use stellar_xdr::curr::LedgerEntry;
+
+/// Latest state of an account changed by a ledger
+pub(super) enum AccountWrite {
+    /// Created or updated, along with its signers
+    Upsert(account::ActiveModel, Vec<account_signer::ActiveModel>),
+    /// Removed by a merge, along with its state when the ledger changed it
+    /// before removing it
+    Delete(Option<account::ActiveModel>),
+}
+
+fn upsert_columns() -> OnConflict {
+    OnConflict::column(account::Column::Id)
+        .update_columns([
+            account::Column::LastModified,
+            account::Column::Balance,
+            account::Column::BuyingLiabilities,
+            account::Column::HomeDomain,
+            account::Column::InflationDestination,
+            account::Column::MasterWeight,
+            account::Column::NumberOfSubentries,
+            account::Column::SellingLiabilities,
+            account::Column::SequenceNumber,
+            account::Column::ThresholdLow,
+            account::Column::ThresholdMedium,
+            account::Column::ThresholdHigh,
+            account::Column::Flags,
+            account::Column::Sponsor,
+            account::Column::NumberOfSponsored,
+            account::Column::NumberOfSponsoring,
+            account::Column::SequenceLedger,
+            account::Column::SequenceTime,
+            account::Column::DeletedInLedger,
+        ])
+        .to_owned()
+}
+
+/// Writes the accounts changed by a ledger along with their history. Accounts
+/// removed by a merge are kept, marked with the ledger they were deleted in
+/// and without signers
+pub(super) async fn write_accounts(
+    db: &DatabaseTransaction,
     ledger_sequence: i32,
-    metrics: &IngestionMetrics,
+    accounts: HashMap<String, AccountWrite>,
+    history: Vec<account_history::ActiveModel>,
 ) -> Result<(), IngestionError> {
-    // Query all accounts with lastmodified = ledger_sequence
-    let updated_accounts = Accounts::find()
-        .filter(stellar_node_entities::accounts::Column::Lastmodified.eq(ledger_sequence))
-        .all(node_database.as_inner())
-        .await?;
+    let account_ids: Vec<String> = accounts.keys().cloned().collect();
+    let mut upserts = Vec::new();
+    let mut signers = Vec::new();
+    let mut deleted = Vec::new();
 
-    let count = updated_accounts.len();
+    for (account_id, write) in accounts {
+        match write {
+            AccountWrite::Upsert(account, account_signers) => {
+                upserts.push(account);
+                signers.extend(account_signers);
+            }
+            AccountWrite::Delete(Some(mut account)) => {
+                account.deleted_in_ledger = Set(Some(ledger_sequence));
+                upserts.push(account);
+            }
+            AccountWrite::Delete(None) => deleted.push(account_id),
+        }
+    }
 
-    // Ingest all updated accounts
-    for account in updated_accounts {
-        ingest_account(quasar_database, account).await?;
+    for chunk in upserts.chunks(BATCH_SIZE) {
+        account::Entity::insert_many(chunk.to_vec())
+            .on_conflict(upsert_columns())
+            .exec_without_returning(db)
+            .await?;
+    }
+    for chunk in deleted.chunks(BATCH_SIZE) {
+        account::Entity::update_many()
+            .col_expr(
+                account::Column::DeletedInLedger,
+                Expr::value(ledger_sequence),
+            )
+            .filter(account::Column::Id.is_in(chunk.to_vec()))
+            .exec(db)
+            .await?;
+    }
 
-        metrics.accounts.inc();
+    for chunk in account_ids.chunks(BATCH_SIZE) {
+        account_signer::Entity::delete_many()
+            .filter(account_signer::Column::AccountId.is_in(chunk.to_vec()))
+            .exec(db)
+            .await?;
+    }
+    for chunk in signers.chunks(BATCH_SIZE) {
+        account_signer::Entity::insert_many(chunk.to_vec())
+            .exec(db)
+            .await?;
     }
 
-    info!("Ingested {} updated accounts", count);
+    for chunk in history.chunks(BATCH_SIZE) {
+        account_history::Entity::insert_many(chunk.to_vec())
+            .exec(db)
+            .await?;
+    }
 
     Ok(())
 }
 
-pub(super) async fn ingest_account(
+/// Inserts an account unless it was already ingested, with its signers and a
+/// first snapshot in its history
+pub(super) async fn insert_account(
     db: &QuasarDatabase,
-    account: accounts::Model,
+    entry: LedgerEntry,
 ) -> Result<(), IngestionError> {
-    let account: account::ActiveModel = account::ActiveModel::try_from(account)?;
+    let ledger_sequence = entry.last_modified_ledger_seq as i32;
+    let signers = account_signer::signers_from_account(&entry)?;
+    let account: account::ActiveModel = account::ActiveModel::try_from(entry)?;
+    let account_id = account
+        .id
+        .clone()
+        .take()
+        .ok_or(IngestionError::MissingAccountId)?;
+    let snapshot = account_history::ActiveModel::from_account(&account, ledger_sequence, None);
 
-    account::Entity::insert(account)
+    let inserted = account::Entity::insert(account)
         .on_conflict(
             OnConflict::column(account::Column::Id)
-                .update_columns([
-                    account::Column::LastModified,
-                    account::Column::Balance,
-                    account::Column::BuyingLiabilities,
-                    account::Column::HomeDomain,
-                    account::Column::InflationDestination,
-                    account::Column::MasterWeight,
-                    account::Column::NumberOfSubentries,
-                    account::Column::SellingLiabilities,
-                    account::Column::SequenceNumber,
-                ])
+                .do_nothing()
                 .to_owned(),
         )
+        .exec_without_returning(db.as_inner())
+        .await?;
+    if inserted == 0 {
+        return Ok(());
+    }
+
+    replace_signers(db, &account_id, signers).await?;
+    account_history::Entity::insert(snapshot)
         .exec(db.as_inner())
         .await?;
+
+    Ok(())
+}
+
+async fn replace_signers(
+    db: &QuasarDatabase,
+    account_id: &str,
+    signers: Vec<account_signer::ActiveModel>,
+) -> Result<(), IngestionError> {
+    account_signer::Entity::delete_many()
+        .filter(account_signer::Column::AccountId.eq(account_id))
+        .exec(db.as_inner())
+        .await?;
+
+    if !signers.is_empty() {
+        account_signer::Entity::insert_many(signers)
+            .exec(db.as_inner())
+            .await?;
+    }
+
     Ok(())
 }
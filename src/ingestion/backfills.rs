@@ -0,0 +1,405 @@
+use log::{error, info, warn};
+use quasar_entities::{backfill, event, ledger, operation, transaction};
+use sea_orm::{
+    sea_query::Expr, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder,
+};
+
+use stellar_node_entities::{ledgerheaders, txhistory};
+use stellar_xdr::curr::{Limits, ReadXdr, TransactionEnvelope, TransactionMeta};
+
+use crate::databases::{NodeDatabase, QuasarDatabase};
+
+use super::{
+    node_state::load_node_state, operations::envelope_operations, transactions::transaction_result,
+    IngestionError,
+};
+
+/// Ids or ledgers rewritten at once
+const CHUNK_SIZE: i64 = 1000;
+
+/// Values of the events, decoded again from their XDR
+const EVENT_VALUES: &str = "event_values";
+/// XDR of the topics and values of the events ingested before it was stored,
+/// read from the transaction history of the node
+const EVENT_XDR: &str = "event_xdr";
+/// Close time, transaction set hash and activity counts of the ledgers
+/// ingested before they were stored, read from the ledger headers and the
+/// transaction history of the node
+const LEDGER_CLOSE_METADATA: &str = "ledger_close_metadata";
+/// Accounts, account data and contracts created before the first ingested
+/// ledger, loaded at once from the state of the node
+const NODE_STATE: &str = "node_state";
+/// Contracts called by the operations ingested before they were stored, read
+/// from the transaction history of the node
+const OPERATION_CONTRACTS: &str = "operation_contracts";
+/// Fee charged and success of the transactions ingested before they were
+/// stored, read from the transaction history of the node
+const TRANSACTION_RESULTS: &str = "transaction_results";
+
+/// Runs the backfills registered by the migrations until they reach their end
+/// position, alongside the ingestion of new ledgers
+pub(super) async fn run_backfills(node_database: NodeDatabase, quasar_database: QuasarDatabase) {
+    let backfills = backfill::Entity::find()
+        .filter(Expr::col(backfill::Column::Position).lt(Expr::col(backfill::Column::EndPosition)))
+        .all(quasar_database.as_inner())
+        .await;
+
+    let backfills = match backfills {
+        Ok(backfills) => backfills,
+        Err(err) => {
+            error!("Failed to load backfills: {}", err);
+            return;
+        }
+    };
+
+    for backfill in backfills {
+        let name = backfill.name.clone();
+        if let Err(err) = run_backfill(&node_database, &quasar_database, backfill).await {
+            error!("Backfill {} failed: {}", name, err);
+        }
+    }
+}
+
+async fn run_backfill(
+    node_database: &NodeDatabase,
+    quasar_database: &QuasarDatabase,
+    backfill: backfill::Model,
+) -> Result<(), IngestionError> {
+    let db = quasar_database.as_inner();
+    info!(
+        "Running backfill {} from {} to {}",
+        backfill.name, backfill.position, backfill.end_position
+    );
+
+    let mut position = backfill.position;
+    while position < backfill.end_position {
+        let end = (position + CHUNK_SIZE).min(backfill.end_position);
+
+        match backfill.name.as_str() {
+            EVENT_VALUES => backfill_event_values(db, position, end).await?,
+            EVENT_XDR => backfill_event_xdr(node_database, db, position, end).await?,
+            LEDGER_CLOSE_METADATA => {
+                backfill_ledger_close_metadata(node_database, db, position, end).await?
+            }
+            NODE_STATE => load_node_state(node_database, quasar_database).await?,
+            OPERATION_CONTRACTS => {
+                backfill_operation_contracts(node_database, db, position, end).await?
+            }
+            TRANSACTION_RESULTS => {
+                backfill_transaction_results(node_database, db, position, end).await?
+            }
+            name => {
+                warn!("Unknown backfill {}", name);
+                return Ok(());
+            }
+        }
+
+        position = end;
+        backfill::Entity::update_many()
+            .col_expr(backfill::Column::Position, Expr::value(position))
+            .filter(backfill::Column::Name.eq(&backfill.name))
+            .exec(db)
+            .await?;
+    }
+
+    info!("Backfill {} done", backfill.name);
+    Ok(())
+}
+
+/// Decodes again the values of the events with ids in `(from, to]`
+async fn backfill_event_values(
+    db: &DatabaseConnection,
+    from: i64,
+    to: i64,
+) -> Result<(), IngestionError> {
+    let events = event::Entity::find()
+        .filter(event::Column::Id.gt(from))
+        .filter(event::Column::Id.lte(to))
+        .filter(event::Column::RawValue.is_not_null())
+        .all(db)
+        .await?;
+
+    for event in events {
+        let raw_value = event.raw_value.as_deref().unwrap_or_default();
+        let value = match event::decode_value(raw_value) {
+            Ok(value) => value,
+            Err(err) => {
+                warn!("Failed to decode the value of event {}: {}", event.id, err);
+                continue;
+            }
+        };
+
+        if value != event.value {
+            event::Entity::update_many()
+                .col_expr(event::Column::Value, Expr::value(value))
+                .filter(event::Column::Id.eq(event.id))
+                .exec(db)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Stores the close time and transaction set hash of the ledgers in
+/// `(from, to]` which the node still has headers of, copies the close time to
+/// their transactions, operations and events, and counts their
+/// transactions and operations. Success counts stay unknown for the ledgers
+/// whose transaction results the node no longer holds
+async fn backfill_ledger_close_metadata(
+    node_database: &NodeDatabase,
+    db: &DatabaseConnection,
+    from: i64,
+    to: i64,
+) -> Result<(), IngestionError> {
+    let headers = ledgerheaders::Entity::find()
+        .filter(ledgerheaders::Column::Ledgerseq.gt(from))
+        .filter(ledgerheaders::Column::Ledgerseq.lte(to))
+        .all(node_database.as_inner())
+        .await?;
+
+    for header in headers {
+        let sequence = header
+            .ledgerseq
+            .ok_or(IngestionError::MissingLedgerSequence)?;
+
+        // Both columns are always set by the conversion
+        let decoded = ledger::ActiveModel::try_from(header)?;
+        ledger::Entity::update_many()
+            .col_expr(
+                ledger::Column::ClosedAt,
+                Expr::value(decoded.closed_at.unwrap()),
+            )
+            .col_expr(
+                ledger::Column::TxSetHash,
+                Expr::value(decoded.tx_set_hash.unwrap()),
+            )
+            .filter(ledger::Column::Sequence.eq(sequence))
+            .filter(ledger::Column::ClosedAt.is_null())
+            .exec(db)
+            .await?;
+    }
+
+    // Rows of the ledgers take their close time, which `closedAt` filters use
+    transaction::Entity::update_many()
+        .col_expr(
+            transaction::Column::ClosedAt,
+            Expr::cust(
+                "(SELECT closed_at FROM ledgers \
+                 WHERE ledgers.sequence = transactions.ledger_sequence)",
+            ),
+        )
+        .filter(transaction::Column::LedgerSequence.gt(from))
+        .filter(transaction::Column::LedgerSequence.lte(to))
+        .filter(transaction::Column::ClosedAt.is_null())
+        .exec(db)
+        .await?;
+    operation::Entity::update_many()
+        .col_expr(
+            operation::Column::ClosedAt,
+            Expr::cust(
+                "(SELECT closed_at FROM transactions \
+                 WHERE transactions.id = operations.transaction_id)",
+            ),
+        )
+        .filter(Expr::cust_with_values(
+            "operations.transaction_id IN (SELECT id FROM transactions \
+             WHERE ledger_sequence > $1 AND ledger_sequence <= $2)",
+            [from, to],
+        ))
+        .filter(operation::Column::ClosedAt.is_null())
+        .exec(db)
+        .await?;
+    event::Entity::update_many()
+        .col_expr(
+            event::Column::ClosedAt,
+            Expr::cust(
+                "(SELECT closed_at FROM transactions \
+                 WHERE transactions.id = events.transaction_id)",
+            ),
+        )
+        .filter(Expr::cust_with_values(
+            "events.transaction_id IN (SELECT id FROM transactions \
+             WHERE ledger_sequence > $1 AND ledger_sequence <= $2)",
+            [from, to],
+        ))
+        .filter(event::Column::ClosedAt.is_null())
+        .exec(db)
+        .await?;
+
+    // The success counts need the results of every transaction of the ledger
+    backfill_transaction_results(node_database, db, from, to).await?;
+
+    ledger::Entity::update_many()
+        .col_expr(
+            ledger::Column::TransactionCount,
+            Expr::cust(
+                "(SELECT count(*)::integer FROM transactions \
+                 WHERE transactions.ledger_sequence = ledgers.sequence)",
+            ),
+        )
+        .col_expr(
+            ledger::Column::OperationCount,
+            Expr::cust(
+                "(SELECT coalesce(sum(operation_count), 0)::integer FROM transactions \
+                 WHERE transactions.ledger_sequence = ledgers.sequence)",
+            ),
+        )
+        .col_expr(
+            ledger::Column::SuccessfulTransactionCount,
+            Expr::cust(
+                "(SELECT CASE WHEN bool_and(successful IS NOT NULL) IS FALSE THEN NULL \
+                 ELSE (count(*) FILTER (WHERE successful))::integer END FROM transactions \
+                 WHERE transactions.ledger_sequence = ledgers.sequence)",
+            ),
+        )
+        .col_expr(
+            ledger::Column::FailedTransactionCount,
+            Expr::cust(
+                "(SELECT CASE WHEN bool_and(successful IS NOT NULL) IS FALSE THEN NULL \
+                 ELSE (count(*) FILTER (WHERE NOT successful))::integer END FROM transactions \
+                 WHERE transactions.ledger_sequence = ledgers.sequence)",
+            ),
+        )
+        .filter(ledger::Column::Sequence.gt(from))
+        .filter(ledger::Column::Sequence.lte(to))
+        .filter(ledger::Column::TransactionCount.is_null())
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Stores the fee charged and success of the transactions of the ledgers in
+/// `(from, to]` which the node still has in its history, the others are left
+/// unknown
+async fn backfill_transaction_results(
+    node_database: &NodeDatabase,
+    db: &DatabaseConnection,
+    from: i64,
+    to: i64,
+) -> Result<(), IngestionError> {
+    let transactions = txhistory::Entity::find()
+        .filter(txhistory::Column::Ledgerseq.gt(from))
+        .filter(txhistory::Column::Ledgerseq.lte(to))
+        .all(node_database.as_inner())
+        .await?;
+
+    for node_transaction in transactions {
+        let (fee_charged, successful) = transaction_result(&node_transaction.txresult)?;
+
+        transaction::Entity::update_many()
+            .col_expr(transaction::Column::FeeCharged, Expr::value(fee_charged))
+            .col_expr(transaction::Column::Successful, Expr::value(successful))
+            .filter(transaction::Column::Id.eq(node_transaction.txid))
+            .filter(transaction::Column::Successful.is_null())
+            .exec(db)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Stores the XDR of the events of the ledgers in `(from, to]` which the node
+/// still has in its history, matching the events of every transaction to its
+/// meta in emission order
+async fn backfill_event_xdr(
+    node_database: &NodeDatabase,
+    db: &DatabaseConnection,
+    from: i64,
+    to: i64,
+) -> Result<(), IngestionError> {
+    let transactions = txhistory::Entity::find()
+        .filter(txhistory::Column::Ledgerseq.gt(from))
+        .filter(txhistory::Column::Ledgerseq.lte(to))
+        .all(node_database.as_inner())
+        .await?;
+
+    for node_transaction in transactions {
+        let contract_events =
+            match TransactionMeta::from_xdr_base64(&node_transaction.txmeta, Limits::none())? {
+                TransactionMeta::V3(v3) => match v3.soroban_meta {
+                    Some(meta) => meta.events,
+                    None => continue,
+                },
+                _ => continue,
+            };
+
+        let events = event::Entity::find()
+            .filter(event::Column::TransactionId.eq(&node_transaction.txid))
+            .order_by_asc(event::Column::Id)
+            .all(db)
+            .await?;
+
+        if events.len() != contract_events.len() {
+            warn!(
+                "Transaction {} has {} events but {} in its meta, skipping",
+                node_transaction.txid,
+                events.len(),
+                contract_events.len()
+            );
+            continue;
+        }
+
+        for (event, contract_event) in events.into_iter().zip(contract_events.iter()) {
+            if event.raw_value.is_some() {
+                continue;
+            }
+
+            // Every column decoded from the XDR is set by the conversion
+            let decoded = event::ActiveModel::try_from(contract_event.clone())?;
+            event::Entity::update_many()
+                .col_expr(event::Column::Value, Expr::value(decoded.value.unwrap()))
+                .col_expr(
+                    event::Column::RawTopics,
+                    Expr::value(decoded.raw_topics.unwrap()),
+                )
+                .col_expr(
+                    event::Column::RawValue,
+                    Expr::value(decoded.raw_value.unwrap()),
+                )
+                .filter(event::Column::Id.eq(event.id))
+                .exec(db)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Stores the contract called by the `InvokeHostFunction` operations of the
+/// ledgers in `(from, to]` which the node still has in its history
+async fn backfill_operation_contracts(
+    node_database: &NodeDatabase,
+    db: &DatabaseConnection,
+    from: i64,
+    to: i64,
+) -> Result<(), IngestionError> {
+    let transactions = txhistory::Entity::find()
+        .filter(txhistory::Column::Ledgerseq.gt(from))
+        .filter(txhistory::Column::Ledgerseq.lte(to))
+        .all(node_database.as_inner())
+        .await?;
+
+    for node_transaction in transactions {
+        let envelope =
+            TransactionEnvelope::from_xdr_base64(&node_transaction.txbody, Limits::none())?;
+
+        for (index, operation) in envelope_operations(envelope).into_iter().enumerate() {
+            let contract_id = match operation::ActiveModel::try_from(operation)?.contract_id {
+                ActiveValue::Set(Some(contract_id)) => contract_id,
+                _ => continue,
+            };
+
+            operation::Entity::update_many()
+                .col_expr(operation::Column::ContractId, Expr::value(contract_id))
+                .filter(operation::Column::TransactionId.eq(&node_transaction.txid))
+                .filter(operation::Column::ApplicationOrder.eq(index as i32 + 1))
+                .exec(db)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
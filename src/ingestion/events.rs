@@ -1,13 +1,14 @@
 use quasar_entities::event;
-use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+use sea_orm::{prelude::DateTimeWithTimeZone, ActiveModelTrait, DatabaseTransaction, Set};
 use stellar_xdr::curr::TransactionMeta;
 
 use super::{IngestionError, IngestionMetrics};
 
 pub(super) async fn ingest_events(
-    db: &DatabaseConnection,
+    db: &DatabaseTransaction,
     transaction_meta: TransactionMeta,
     transaction_id: &str,
+    closed_at: Option<DateTimeWithTimeZone>,
     metrics: &IngestionMetrics,
 ) -> Result<(), IngestionError> {
     match transaction_meta {
@@ -21,6 +22,7 @@ pub(super) async fn ingest_events(
                     let mut event: event::ActiveModel =
                         event::ActiveModel::try_from(event.clone())?;
                     event.transaction_id = Set(transaction_id.to_owned());
+                    event.closed_at = Set(closed_at);
                     event.insert(db).await?;
 
                     metrics.events.inc();
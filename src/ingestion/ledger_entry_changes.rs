@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+
+use log::info;
+use quasar_entities::{
+    account::{self, account_id_to_string},
+    account_data, account_history, account_signer, contract,
+};
+use sea_orm::{ColumnTrait, DatabaseTransaction, EntityTrait, QueryFilter, QueryOrder};
+use stellar_node_entities::{
+    prelude::{Txfeehistory, Upgradehistory},
+    txfeehistory, upgradehistory,
+};
+use stellar_xdr::curr::{
+    ConfigSettingEntry, LedgerEntry, LedgerEntryChange, LedgerEntryChanges, LedgerEntryData,
+    LedgerKey, LedgerKeyData, Limits, OperationMeta, ReadXdr, TransactionMeta,
+};
+
+use crate::databases::NodeDatabase;
+
+use super::{
+    account_data::{data_key, write_account_data},
+    accounts::{write_accounts, AccountWrite},
+    contracts::upsert_contracts,
+    network_config::update_network_config,
+    IngestionError, IngestionMetrics,
+};
+
+/// A ledger entry change along with the transaction that caused it, upgrade
+/// changes are not caused by a transaction
+pub(super) struct LedgerChange {
+    pub transaction_id: Option<String>,
+    pub change: LedgerEntryChange,
+}
+
+/// Rows written by a single statement
+pub(super) const BATCH_SIZE: usize = 1000;
+
+/// Applies the ledger entry changes of a ledger in the order the Stellar node
+/// applied them: fees, then transactions, then protocol upgrades
+pub(super) async fn ingest_ledger_entry_changes(
+    node_database: &NodeDatabase,
+    db: &DatabaseTransaction,
+    ledger_sequence: i32,
+    transaction_changes: Vec<LedgerChange>,
+    metrics: &IngestionMetrics,
+) -> Result<(), IngestionError> {
+    let mut changes = fee_changes(node_database, ledger_sequence).await?;
+    changes.extend(transaction_changes);
+    changes.extend(upgrade_changes(node_database, ledger_sequence).await?);
+
+    let count = changes.len();
+
+    let mut writes = LedgerWrites::default();
+    for LedgerChange {
+        transaction_id,
+        change,
+    } in changes
+    {
+        writes
+            .add(db, ledger_sequence, transaction_id, change, metrics)
+            .await?;
+    }
+    writes.write(db, ledger_sequence).await?;
+
+    info!("Applied {} ledger entry changes", count);
+
+    Ok(())
+}
+
+/// Collects the ledger entry changes of a transaction in the order they were applied
+pub(super) fn ledger_entry_changes(transaction_meta: &TransactionMeta) -> Vec<LedgerEntryChange> {
+    match transaction_meta {
+        TransactionMeta::V0(operations) => operation_changes(operations).collect(),
+        TransactionMeta::V1(v1) => changes(&v1.tx_changes)
+            .chain(operation_changes(&v1.operations))
+            .collect(),
+        TransactionMeta::V2(v2) => changes(&v2.tx_changes_before)
+            .chain(operation_changes(&v2.operations))
+            .chain(changes(&v2.tx_changes_after))
+            .collect(),
+        TransactionMeta::V3(v3) => changes(&v3.tx_changes_before)
+            .chain(operation_changes(&v3.operations))
+            .chain(changes(&v3.tx_changes_after))
+            .collect(),
+    }
+}
+
+fn changes(changes: &LedgerEntryChanges) -> impl Iterator<Item = LedgerEntryChange> + '_ {
+    changes.0.iter().cloned()
+}
+
+fn operation_changes(operations: &[OperationMeta]) -> impl Iterator<Item = LedgerEntryChange> + '_ {
+    operations
+        .iter()
+        .flat_map(|operation| changes(&operation.changes))
+}
+
+async fn fee_changes(
+    node_database: &NodeDatabase,
+    ledger_sequence: i32,
+) -> Result<Vec<LedgerChange>, IngestionError> {
+    let fees = Txfeehistory::find()
+        .filter(txfeehistory::Column::Ledgerseq.eq(ledger_sequence))
+        .order_by_asc(txfeehistory::Column::Txindex)
+        .all(node_database.as_inner())
+        .await?;
+
+    let mut fee_changes = Vec::new();
+
+    for fee in fees {
+        let changes = LedgerEntryChanges::from_xdr_base64(&fee.txchanges, Limits::none())?;
+        fee_changes.extend(changes.0.iter().cloned().map(|change| LedgerChange {
+            transaction_id: Some(fee.txid.clone()),
+            change,
+        }));
+    }
+
+    Ok(fee_changes)
+}
+
+async fn upgrade_changes(
+    node_database: &NodeDatabase,
+    ledger_sequence: i32,
+) -> Result<Vec<LedgerChange>, IngestionError> {
+    let upgrades = Upgradehistory::find()
+        .filter(upgradehistory::Column::Ledgerseq.eq(ledger_sequence))
+        .order_by_asc(upgradehistory::Column::Upgradeindex)
+        .all(node_database.as_inner())
+        .await?;
+
+    let mut upgrade_changes = Vec::new();
+
+    for upgrade in upgrades {
+        let changes = LedgerEntryChanges::from_xdr_base64(&upgrade.changes, Limits::none())?;
+        upgrade_changes.extend(changes.0.iter().cloned().map(|change| LedgerChange {
+            transaction_id: None,
+            change,
+        }));
+    }
+
+    Ok(upgrade_changes)
+}
+
+/// Final state of the entries changed by a ledger, its changes being folded in
+/// application order before they are written at once
+#[derive(Default)]
+struct LedgerWrites {
+    accounts: HashMap<String, AccountWrite>,
+    account_history: Vec<account_history::ActiveModel>,
+    account_data: HashMap<(String, String), Option<account_data::ActiveModel>>,
+    contracts: HashMap<String, contract::ActiveModel>,
+    config_settings: Vec<ConfigSettingEntry>,
+}
+
+impl LedgerWrites {
+    async fn add(
+        &mut self,
+        db: &DatabaseTransaction,
+        ledger_sequence: i32,
+        transaction_id: Option<String>,
+        change: LedgerEntryChange,
+        metrics: &IngestionMetrics,
+    ) -> Result<(), IngestionError> {
+        match change {
+            LedgerEntryChange::Created(entry) | LedgerEntryChange::Updated(entry) => {
+                self.add_entry(ledger_sequence, transaction_id, entry, metrics)
+            }
+            LedgerEntryChange::Removed(key) => {
+                self.remove_entry(db, ledger_sequence, transaction_id, key)
+                    .await
+            }
+            // The state of an entry right before it is updated or removed, the
+            // change that follows it carries the new state
+            LedgerEntryChange::State(_) => Ok(()),
+        }
+    }
+
+    fn add_entry(
+        &mut self,
+        ledger_sequence: i32,
+        transaction_id: Option<String>,
+        entry: LedgerEntry,
+        metrics: &IngestionMetrics,
+    ) -> Result<(), IngestionError> {
+        match &entry.data {
+            LedgerEntryData::Account(_) => {
+                let signers = account_signer::signers_from_account(&entry)?;
+                let account = account::ActiveModel::try_from(entry)?;
+                let account_id = account
+                    .id
+                    .clone()
+                    .take()
+                    .ok_or(IngestionError::MissingAccountId)?;
+
+                self.account_history
+                    .push(account_history::ActiveModel::from_account(
+                        &account,
+                        ledger_sequence,
+                        transaction_id,
+                    ));
+                self.accounts
+                    .insert(account_id, AccountWrite::Upsert(account, signers));
+                metrics.accounts.inc();
+            }
+            LedgerEntryData::Data(data) => {
+                let key = data_key(&LedgerKeyData {
+                    account_id: data.account_id.clone(),
+                    data_name: data.data_name.clone(),
+                })?;
+                self.account_data
+                    .insert(key, Some(account_data::ActiveModel::try_from(entry)?));
+                metrics.account_data.inc();
+            }
+            LedgerEntryData::ContractData(_) => {
+                let contract = contract::ActiveModel::try_from(entry)?;
+                // Always set by the conversion
+                let address = contract.address.clone().unwrap();
+                self.contracts.insert(address, contract);
+                metrics.contracts.inc();
+            }
+            LedgerEntryData::ConfigSetting(setting) => {
+                self.config_settings.push(setting.clone());
+            }
+            // Not mirrored in the Quasar database
+            LedgerEntryData::Trustline(_)
+            | LedgerEntryData::Offer(_)
+            | LedgerEntryData::ClaimableBalance(_)
+            | LedgerEntryData::LiquidityPool(_)
+            | LedgerEntryData::ContractCode(_)
+            | LedgerEntryData::Ttl(_) => {}
+        }
+
+        Ok(())
+    }
+
+    async fn remove_entry(
+        &mut self,
+        db: &DatabaseTransaction,
+        ledger_sequence: i32,
+        transaction_id: Option<String>,
+        key: LedgerKey,
+    ) -> Result<(), IngestionError> {
+        match key {
+            LedgerKey::Account(key) => {
+                let account_id = account_id_to_string(&key.account_id);
+
+                // The state of the account changed by the ledger is not
+                // written yet
+                let state = match self.accounts.remove(&account_id) {
+                    Some(AccountWrite::Upsert(account, _)) => Some(account),
+                    Some(AccountWrite::Delete(account)) => account,
+                    None => None,
+                };
+                let sequence_number = match &state {
+                    Some(account) => Some(account.sequence_number.clone().unwrap()),
+                    None => account::Entity::find_by_id(account_id.clone())
+                        .one(db)
+                        .await?
+                        .map(|account| account.sequence_number),
+                };
+
+                if let Some(sequence_number) = sequence_number {
+                    self.account_history
+                        .push(account_history::ActiveModel::from_deleted_account(
+                            account_id.clone(),
+                            sequence_number,
+                            ledger_sequence,
+                            transaction_id,
+                        ));
+                }
+                self.accounts
+                    .insert(account_id, AccountWrite::Delete(state));
+            }
+            LedgerKey::Data(key) => {
+                self.account_data.insert(data_key(&key)?, None);
+            }
+            // A contract stays known when one of its data entries is removed, the
+            // other entry types are not mirrored in the Quasar database
+            LedgerKey::ContractData(_)
+            | LedgerKey::Trustline(_)
+            | LedgerKey::Offer(_)
+            | LedgerKey::ClaimableBalance(_)
+            | LedgerKey::LiquidityPool(_)
+            | LedgerKey::ContractCode(_)
+            | LedgerKey::ConfigSetting(_)
+            | LedgerKey::Ttl(_) => {}
+        }
+
+        Ok(())
+    }
+
+    async fn write(
+        self,
+        db: &DatabaseTransaction,
+        ledger_sequence: i32,
+    ) -> Result<(), IngestionError> {
+        write_accounts(db, ledger_sequence, self.accounts, self.account_history).await?;
+        write_account_data(db, self.account_data).await?;
+        upsert_contracts(db, self.contracts.into_values().collect()).await?;
+        update_network_config(db, ledger_sequence, &self.config_settings).await?;
+        Ok(())
+    }
+}
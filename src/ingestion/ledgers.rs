@@ -1,10 +1,18 @@
 use crate::databases::{NodeDatabase, QuasarDatabase};
-use crate::ingestion::contracts::ingest_contracts;
-use crate::ingestion::{accounts::ingest_accounts, transactions::ingest_transactions};
+use crate::ingestion::{
+    ledger_entry_changes::ingest_ledger_entry_changes,
+    quorum_sets::sync_quorum_sets,
+    transactions::{ingest_transactions, LedgerActivity},
+    upgrades::ingest_upgrades,
+};
+use crate::notifications::{Notification, Notifier};
 use log::info;
-use quasar_entities::{ledger, prelude::Ledger};
-use sea_orm::{ActiveModelTrait, ColumnTrait, QueryFilter};
-use sea_orm::{DatabaseConnection, DbErr, EntityTrait, QueryOrder};
+use migration::Expr;
+use quasar_entities::{event, ledger, prelude::Ledger, transaction};
+use sea_orm::{ActiveModelTrait, ColumnTrait, JoinType, QueryFilter, QuerySelect, RelationTrait};
+use sea_orm::{
+    DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait, QueryOrder, TransactionTrait,
+};
 use stellar_node_entities::ledgerheaders;
 use stellar_node_entities::prelude::Ledgerheaders;
 
@@ -62,6 +70,7 @@ pub(super) async fn ingest_ledgers(
     quasar_database: &QuasarDatabase,
     mut last_ingested_ledger_sequence: Option<i32>,
     metrics: &IngestionMetrics,
+    notifier: &Notifier,
 ) -> Result<(), IngestionError> {
     while let Some(next_ledger) =
         next_ledger_to_ingest(node_database, last_ingested_ledger_sequence).await?
@@ -71,8 +80,14 @@ pub(super) async fn ingest_ledgers(
         last_ingested_ledger_sequence = Some(ingested_sequence);
 
         metrics.ledgers.inc();
+
+        if notifier.has_subscribers() {
+            notify_ledger(quasar_database, notifier, ingested_sequence).await?;
+        }
     }
 
+    sync_quorum_sets(node_database, quasar_database).await?;
+
     Ok(())
 }
 
@@ -87,20 +102,92 @@ async fn handle_new_ledger(
         .ok_or(IngestionError::MissingLedgerSequence)?;
     info!("Ingesting ledger {} and associated data", sequence);
 
-    ingest_ledger(ledger, quasar_database).await?;
-    ingest_accounts(node_database, quasar_database, sequence, metrics).await?;
-    ingest_transactions(node_database, quasar_database, sequence, metrics).await?;
-    ingest_contracts(node_database, quasar_database, metrics).await?;
+    // The next ledger to ingest follows the last one stored, a ledger is
+    // stored entirely or not at all
+    let db = quasar_database.begin().await?;
+
+    let ledger = ingest_ledger(ledger, &db).await?;
+    let (changes, activity) =
+        ingest_transactions(node_database, &db, sequence, ledger.closed_at, metrics).await?;
+    record_ledger_activity(&db, sequence, activity).await?;
+    ingest_ledger_entry_changes(node_database, &db, sequence, changes, metrics).await?;
+    ingest_upgrades(node_database, &db, sequence, metrics).await?;
+
+    db.commit().await?;
 
     Ok(sequence)
 }
 
 async fn ingest_ledger(
     ledger: ledgerheaders::Model,
-    quasar_database: &DatabaseConnection,
-) -> Result<(), IngestionError> {
+    db: &DatabaseTransaction,
+) -> Result<ledger::Model, IngestionError> {
     let ledger: ledger::ActiveModel = ledger::ActiveModel::try_from(ledger)?;
-    ledger.insert(quasar_database).await?;
+    Ok(ledger.insert(db).await?)
+}
+
+async fn record_ledger_activity(
+    db: &DatabaseTransaction,
+    sequence: i32,
+    activity: LedgerActivity,
+) -> Result<(), IngestionError> {
+    Ledger::update_many()
+        .col_expr(
+            ledger::Column::TransactionCount,
+            Expr::value(activity.transaction_count),
+        )
+        .col_expr(
+            ledger::Column::SuccessfulTransactionCount,
+            Expr::value(activity.successful_transaction_count),
+        )
+        .col_expr(
+            ledger::Column::FailedTransactionCount,
+            Expr::value(activity.failed_transaction_count),
+        )
+        .col_expr(
+            ledger::Column::OperationCount,
+            Expr::value(activity.operation_count),
+        )
+        .filter(ledger::Column::Sequence.eq(sequence))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Publishes a fully ingested ledger along with its transactions and events
+async fn notify_ledger(
+    quasar_database: &QuasarDatabase,
+    notifier: &Notifier,
+    sequence: i32,
+) -> Result<(), IngestionError> {
+    let db = quasar_database.as_inner();
+
+    let ledger = Ledger::find()
+        .filter(ledger::Column::Sequence.eq(sequence))
+        .one(db)
+        .await?;
+    let transactions = transaction::Entity::find()
+        .filter(transaction::Column::LedgerSequence.eq(sequence))
+        .order_by_asc(transaction::Column::ApplicationOrder)
+        .all(db)
+        .await?;
+    let events = event::Entity::find()
+        .join(JoinType::InnerJoin, event::Relation::Transaction.def())
+        .filter(transaction::Column::LedgerSequence.eq(sequence))
+        .order_by_asc(event::Column::Id)
+        .all(db)
+        .await?;
+
+    if let Some(ledger) = ledger {
+        notifier.notify(Notification::Ledger(ledger));
+    }
+    for transaction in transactions {
+        notifier.notify(Notification::Transaction(transaction));
+    }
+    for event in events {
+        notifier.notify(Notification::Event(event));
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,114 @@
+use log::info;
+use quasar_entities::network_config;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, DatabaseTransaction, EntityTrait,
+    IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder, Set,
+};
+use stellar_node_entities::configsettings;
+use stellar_xdr::curr::{ConfigSettingEntry, LedgerEntry, LedgerEntryData, Limits, ReadXdr};
+
+use crate::databases::{NodeDatabase, QuasarDatabase};
+
+use super::IngestionError;
+
+/// Writes the network config held by the node when none was recorded yet, the
+/// settings changed by later ledgers are applied on top of it
+pub(super) async fn seed_network_config(
+    node_database: &NodeDatabase,
+    quasar_database: &QuasarDatabase,
+) -> Result<(), IngestionError> {
+    if network_config::Entity::find()
+        .count(quasar_database.as_inner())
+        .await?
+        > 0
+    {
+        return Ok(());
+    }
+
+    let settings = configsettings::Entity::find()
+        .all(node_database.as_inner())
+        .await?;
+    let ledger_sequence = match settings.iter().map(|setting| setting.lastmodified).max() {
+        Some(ledger_sequence) => ledger_sequence,
+        None => return Ok(()),
+    };
+
+    let mut config = network_config::ActiveModel {
+        ledger_sequence: Set(ledger_sequence),
+        ..Default::default()
+    };
+    for setting in settings {
+        let entry = LedgerEntry::from_xdr_base64(&setting.ledgerentry, Limits::none())?;
+        if let LedgerEntryData::ConfigSetting(setting) = &entry.data {
+            config.apply_setting(setting);
+        }
+    }
+    config.insert(quasar_database.as_inner()).await?;
+
+    info!("Seeded network config as of ledger {}", ledger_sequence);
+    Ok(())
+}
+
+/// Records the config settings changed by a ledger in its network config,
+/// starting from the configuration of the previous ledger that changed it, or
+/// the seeded one for ledgers before it
+pub(super) async fn update_network_config(
+    db: &DatabaseTransaction,
+    ledger_sequence: i32,
+    settings: &[ConfigSettingEntry],
+) -> Result<(), IngestionError> {
+    if settings.is_empty() {
+        return Ok(());
+    }
+
+    let mut current = network_config::Entity::find()
+        .filter(network_config::Column::LedgerSequence.lte(ledger_sequence))
+        .order_by_desc(network_config::Column::LedgerSequence)
+        .one(db)
+        .await?;
+    // The config seeded from the node is as of its latest change, which can
+    // come after the ledgers ingested first, it is the closest known config
+    if current.is_none() {
+        current = network_config::Entity::find()
+            .order_by_asc(network_config::Column::LedgerSequence)
+            .one(db)
+            .await?;
+    }
+
+    let (mut config, recorded) = match current {
+        Some(config) if config.ledger_sequence == ledger_sequence => {
+            (config.into_active_model(), true)
+        }
+        Some(config) => {
+            let mut config = config.into_active_model();
+            config.ledger_sequence = Set(ledger_sequence);
+            config.created_at = NotSet;
+            (config, false)
+        }
+        // Without any recorded config the settings are those of the upgrade
+        // creating them, which sets all of them at once
+        None => (
+            network_config::ActiveModel {
+                ledger_sequence: Set(ledger_sequence),
+                ..Default::default()
+            },
+            false,
+        ),
+    };
+
+    let mut changed = false;
+    for setting in settings {
+        changed |= config.apply_setting(setting);
+    }
+    if !changed {
+        return Ok(());
+    }
+
+    if recorded {
+        config.update(db).await?;
+    } else {
+        config.insert(db).await?;
+    }
+
+    Ok(())
+}
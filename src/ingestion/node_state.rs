@@ -0,0 +1,246 @@
+use base64::{engine::general_purpose, Engine};
+use log::info;
+use migration::OnConflict;
+use quasar_entities::{account::AccountError, account_data, contract};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, EntityTrait, IntoSimpleExpr, QueryFilter, QueryOrder, QuerySelect,
+};
+use stellar_node_entities::{accountdata, accounts, contractdata};
+use stellar_xdr::curr::{
+    AccountEntry, AccountEntryExt, AccountEntryExtensionV1, AccountEntryExtensionV1Ext, AccountId,
+    DataEntry, DataEntryExt, LedgerEntry, LedgerEntryData, LedgerEntryExt, Liabilities, Limits,
+    PublicKey, ReadXdr, SequenceNumber, Signer, Thresholds, Uint256, VecM,
+};
+
+use crate::databases::{NodeDatabase, QuasarDatabase};
+
+use super::{accounts::insert_account, IngestionError};
+
+/// Rows read at once from the node tables
+const PAGE_SIZE: u64 = 1000;
+
+/// Loads the accounts, account data and contracts the node holds, keeping the
+/// ones already ingested, so the entries created before the first ingested
+/// ledger are known
+pub(super) async fn load_node_state(
+    node_database: &NodeDatabase,
+    quasar_database: &QuasarDatabase,
+) -> Result<(), IngestionError> {
+    load_accounts(node_database, quasar_database).await?;
+    load_account_data(node_database, quasar_database).await?;
+    load_contracts(node_database, quasar_database).await?;
+    Ok(())
+}
+
+async fn load_accounts(
+    node_database: &NodeDatabase,
+    quasar_database: &QuasarDatabase,
+) -> Result<(), IngestionError> {
+    let mut last: Option<String> = None;
+    let mut loaded = 0;
+
+    loop {
+        let mut query = accounts::Entity::find()
+            .order_by_asc(accounts::Column::Accountid)
+            .limit(PAGE_SIZE);
+        if let Some(last) = &last {
+            query = query.filter(accounts::Column::Accountid.gt(last.as_str()));
+        }
+
+        let rows = query.all(node_database.as_inner()).await?;
+        last = match rows.last() {
+            Some(row) => Some(row.accountid.clone()),
+            None => break,
+        };
+
+        loaded += rows.len();
+        for row in rows {
+            insert_account(quasar_database, account_entry(row)?).await?;
+        }
+    }
+
+    info!("Loaded {} accounts from the node", loaded);
+    Ok(())
+}
+
+async fn load_account_data(
+    node_database: &NodeDatabase,
+    quasar_database: &QuasarDatabase,
+) -> Result<(), IngestionError> {
+    let mut last: Option<(String, String)> = None;
+    let mut loaded = 0;
+
+    loop {
+        let mut query = accountdata::Entity::find()
+            .order_by_asc(accountdata::Column::Accountid)
+            .order_by_asc(accountdata::Column::Dataname)
+            .limit(PAGE_SIZE);
+        if let Some((account_id, name)) = &last {
+            query = query.filter(
+                Expr::tuple([
+                    accountdata::Column::Accountid.into_simple_expr(),
+                    accountdata::Column::Dataname.into_simple_expr(),
+                ])
+                .gt(Expr::tuple([
+                    account_id.as_str().into(),
+                    name.as_str().into(),
+                ])),
+            );
+        }
+
+        let rows = query.all(node_database.as_inner()).await?;
+        last = match rows.last() {
+            Some(row) => Some((row.accountid.clone(), row.dataname.clone())),
+            None => break,
+        };
+
+        loaded += rows.len();
+        let entries = rows
+            .into_iter()
+            .map(|row| Ok(account_data::ActiveModel::try_from(data_entry(row)?)?))
+            .collect::<Result<Vec<_>, IngestionError>>()?;
+
+        account_data::Entity::insert_many(entries)
+            .on_conflict(
+                OnConflict::columns([account_data::Column::AccountId, account_data::Column::Name])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(quasar_database.as_inner())
+            .await?;
+    }
+
+    info!("Loaded {} account data entries from the node", loaded);
+    Ok(())
+}
+
+async fn load_contracts(
+    node_database: &NodeDatabase,
+    quasar_database: &QuasarDatabase,
+) -> Result<(), IngestionError> {
+    let mut last: Option<(String, String, i32)> = None;
+    let mut loaded = 0;
+
+    loop {
+        let mut query = contractdata::Entity::find()
+            .order_by_asc(contractdata::Column::Contractid)
+            .order_by_asc(contractdata::Column::Key)
+            .order_by_asc(contractdata::Column::Type)
+            .limit(PAGE_SIZE);
+        if let Some((contract_id, key, r#type)) = &last {
+            query = query.filter(
+                Expr::tuple([
+                    contractdata::Column::Contractid.into_simple_expr(),
+                    contractdata::Column::Key.into_simple_expr(),
+                    contractdata::Column::Type.into_simple_expr(),
+                ])
+                .gt(Expr::tuple([
+                    contract_id.as_str().into(),
+                    key.as_str().into(),
+                    (*r#type).into(),
+                ])),
+            );
+        }
+
+        let rows = query.all(node_database.as_inner()).await?;
+        last = match rows.last() {
+            Some(row) => Some((row.contractid.clone(), row.key.clone(), row.r#type)),
+            None => break,
+        };
+
+        loaded += rows.len();
+        let contracts = rows
+            .into_iter()
+            .map(|row| {
+                let entry = LedgerEntry::from_xdr_base64(row.ledgerentry, Limits::none())?;
+                Ok(contract::ActiveModel::try_from(entry)?)
+            })
+            .collect::<Result<Vec<_>, IngestionError>>()?;
+
+        // Every entry of a contract shares its address, the first one is kept
+        contract::Entity::insert_many(contracts)
+            .on_conflict(
+                OnConflict::column(contract::Column::Address)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(quasar_database.as_inner())
+            .await?;
+    }
+
+    info!("Loaded {} contract entries from the node", loaded);
+    Ok(())
+}
+
+/// Rebuilds the ledger entry of an account from its columns in the node
+fn account_entry(row: accounts::Model) -> Result<LedgerEntry, IngestionError> {
+    let signers = match row.signers {
+        Some(signers) => VecM::<Signer, 20>::from_xdr_base64(signers, Limits::none())?,
+        None => VecM::default(),
+    };
+
+    // The node only stores the extension of accounts with liabilities
+    let ext = match (row.buyingliabilities, row.sellingliabilities) {
+        (Some(buying), Some(selling)) => AccountEntryExt::V1(AccountEntryExtensionV1 {
+            liabilities: Liabilities { buying, selling },
+            ext: match row.extension {
+                Some(extension) => {
+                    AccountEntryExtensionV1Ext::from_xdr_base64(extension, Limits::none())?
+                }
+                None => AccountEntryExtensionV1Ext::V0,
+            },
+        }),
+        _ => AccountEntryExt::V0,
+    };
+
+    let account = AccountEntry {
+        account_id: account_id(&row.accountid)?,
+        balance: row.balance,
+        seq_num: SequenceNumber(row.seqnum),
+        num_sub_entries: row.numsubentries as u32,
+        inflation_dest: row.inflationdest.as_deref().map(account_id).transpose()?,
+        flags: row.flags as u32,
+        home_domain: decode(&row.homedomain)?.try_into()?,
+        // Thresholds are fixed size opaque data, encoded without padding
+        thresholds: Thresholds::from_xdr_base64(row.thresholds, Limits::none())?,
+        signers,
+        ext,
+    };
+
+    Ok(LedgerEntry {
+        last_modified_ledger_seq: row.lastmodified as u32,
+        data: LedgerEntryData::Account(account),
+        ext: LedgerEntryExt::from_xdr_base64(row.ledgerext, Limits::none())?,
+    })
+}
+
+/// Rebuilds the ledger entry of an account data entry from its columns in the
+/// node
+fn data_entry(row: accountdata::Model) -> Result<LedgerEntry, IngestionError> {
+    let data = DataEntry {
+        account_id: account_id(&row.accountid)?,
+        data_name: decode(&row.dataname)?.try_into()?,
+        data_value: decode(&row.datavalue)?.try_into()?,
+        ext: match row.extension {
+            Some(extension) => DataEntryExt::from_xdr_base64(extension, Limits::none())?,
+            None => DataEntryExt::V0,
+        },
+    };
+
+    Ok(LedgerEntry {
+        last_modified_ledger_seq: row.lastmodified as u32,
+        data: LedgerEntryData::Data(data),
+        ext: LedgerEntryExt::from_xdr_base64(row.ledgerext, Limits::none())?,
+    })
+}
+
+fn account_id(strkey: &str) -> Result<AccountId, IngestionError> {
+    let key = stellar_strkey::ed25519::PublicKey::from_string(strkey)?;
+    Ok(AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(key.0))))
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, IngestionError> {
+    Ok(general_purpose::STANDARD
+        .decode(value)
+        .map_err(AccountError::from)?)
+}
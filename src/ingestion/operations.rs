@@ -1,30 +1,36 @@
 use quasar_entities::operation;
-use sea_orm::{ActiveModelTrait, Set};
+use sea_orm::{prelude::DateTimeWithTimeZone, ActiveModelTrait, DatabaseTransaction, Set};
 use stellar_xdr::curr::{Operation, TransactionEnvelope};
 
-use crate::databases::QuasarDatabase;
-
 use super::{IngestionError, IngestionMetrics};
 
+/// Operations of a transaction, in application order
+pub(super) fn envelope_operations(envelope: TransactionEnvelope) -> Vec<Operation> {
+    match envelope {
+        TransactionEnvelope::TxV0(envelope) => envelope.tx.operations.to_vec(),
+        TransactionEnvelope::Tx(envelope) => envelope.tx.operations.to_vec(),
+        TransactionEnvelope::TxFeeBump(_) => vec![],
+    }
+}
+
 pub(super) async fn ingest_operations(
-    db: &QuasarDatabase,
+    db: &DatabaseTransaction,
     transaction_id: &str,
+    closed_at: Option<DateTimeWithTimeZone>,
     transaction_tx_body: TransactionEnvelope,
     metrics: &IngestionMetrics,
 ) -> Result<(), IngestionError> {
-    let operations: Vec<Operation> = match transaction_tx_body {
-        TransactionEnvelope::TxV0(envelope) => envelope.tx.operations.to_vec(),
-        TransactionEnvelope::Tx(envelope) => envelope.tx.operations.to_vec(),
-        TransactionEnvelope::TxFeeBump(_) => vec![],
-    };
-
-    for (index, operation) in operations.into_iter().enumerate() {
+    for (index, operation) in envelope_operations(transaction_tx_body)
+        .into_iter()
+        .enumerate()
+    {
         let mut operation: operation::ActiveModel = operation::ActiveModel::try_from(operation)?;
 
         operation.transaction_id = Set(transaction_id.to_owned());
         operation.application_order = Set(index as i32 + 1);
+        operation.closed_at = Set(closed_at);
 
-        operation.insert(db.as_inner()).await?;
+        operation.insert(db).await?;
 
         metrics.operations.inc();
     }
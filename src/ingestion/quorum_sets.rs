@@ -0,0 +1,56 @@
+use log::info;
+use migration::OnConflict;
+use quasar_entities::{quorum_node, quorum_set};
+use sea_orm::EntityTrait;
+use stellar_node_entities::prelude::{Quoruminfo, Scpquorums};
+
+use crate::databases::{NodeDatabase, QuasarDatabase};
+
+use super::IngestionError;
+
+/// Mirrors the quorum sets known to the Stellar node and the quorum set used by
+/// each node, both only reflect the latest state seen by the node
+pub(super) async fn sync_quorum_sets(
+    node_database: &NodeDatabase,
+    quasar_database: &QuasarDatabase,
+) -> Result<(), IngestionError> {
+    let quorum_sets = Scpquorums::find()
+        .all(node_database.as_inner())
+        .await?
+        .into_iter()
+        .map(quorum_set::ActiveModel::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !quorum_sets.is_empty() {
+        info!("Syncing {} quorum sets", quorum_sets.len());
+
+        quorum_set::Entity::insert_many(quorum_sets)
+            .on_conflict(
+                OnConflict::column(quorum_set::Column::Hash)
+                    .update_column(quorum_set::Column::LastLedgerSequence)
+                    .to_owned(),
+            )
+            .exec(quasar_database.as_inner())
+            .await?;
+    }
+
+    let quorum_nodes = Quoruminfo::find()
+        .all(node_database.as_inner())
+        .await?
+        .into_iter()
+        .map(quorum_node::ActiveModel::from)
+        .collect::<Vec<_>>();
+
+    if !quorum_nodes.is_empty() {
+        quorum_node::Entity::insert_many(quorum_nodes)
+            .on_conflict(
+                OnConflict::column(quorum_node::Column::NodeId)
+                    .update_column(quorum_node::Column::QuorumSetHash)
+                    .to_owned(),
+            )
+            .exec(quasar_database.as_inner())
+            .await?;
+    }
+
+    Ok(())
+}
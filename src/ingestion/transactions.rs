@@ -1,61 +1,119 @@
 use log::info;
 use quasar_entities::transaction;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ColumnTrait, DatabaseTransaction, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
 use stellar_node_entities::{prelude::Txhistory, txhistory};
-use stellar_xdr::curr::{Limits, ReadXdr, TransactionEnvelope, TransactionMeta};
+use stellar_xdr::curr::{
+    Limits, ReadXdr, TransactionEnvelope, TransactionMeta, TransactionResultPair,
+    TransactionResultResult,
+};
 
-use crate::databases::{NodeDatabase, QuasarDatabase};
+use crate::databases::NodeDatabase;
 
 use super::{
-    events::ingest_events, operations::ingest_operations, IngestionError, IngestionMetrics,
+    events::ingest_events,
+    ledger_entry_changes::{ledger_entry_changes, LedgerChange},
+    operations::ingest_operations,
+    IngestionError, IngestionMetrics,
 };
 
+/// Transaction and operation counts of a ledger
+#[derive(Default)]
+pub(super) struct LedgerActivity {
+    pub transaction_count: i32,
+    pub successful_transaction_count: i32,
+    pub failed_transaction_count: i32,
+    pub operation_count: i32,
+}
+
+/// Ingests the transactions of a ledger and returns their ledger entry changes
+/// in application order along with the activity of the ledger
 pub(super) async fn ingest_transactions(
     node_database: &NodeDatabase,
-    quasar_database: &QuasarDatabase,
+    db: &DatabaseTransaction,
     ledger_sequence: i32,
+    closed_at: Option<DateTimeWithTimeZone>,
     metrics: &IngestionMetrics,
-) -> Result<(), IngestionError> {
+) -> Result<(Vec<LedgerChange>, LedgerActivity), IngestionError> {
     // Query all transactions with lastmodified = ledger_sequence
     let updated_transactions = Txhistory::find()
         .filter(stellar_node_entities::txhistory::Column::Ledgerseq.eq(ledger_sequence))
+        .order_by_asc(stellar_node_entities::txhistory::Column::Txindex)
         .all(node_database.as_inner())
         .await?;
 
     let count = updated_transactions.len();
+    let mut changes = Vec::new();
+    let mut activity = LedgerActivity::default();
 
     // Ingest all updated transactions
     for transaction in updated_transactions {
-        ingest_transaction(quasar_database, transaction, metrics).await?;
+        let (transaction_changes, transaction) =
+            ingest_transaction(db, transaction, closed_at, metrics).await?;
+        changes.extend(transaction_changes);
+
+        activity.transaction_count += 1;
+        activity.operation_count += transaction.operation_count;
+        if transaction.successful == Some(true) {
+            activity.successful_transaction_count += 1;
+        } else {
+            activity.failed_transaction_count += 1;
+        }
 
         metrics.transactions.inc();
     }
 
     info!("Ingested {} transactions", count);
 
-    Ok(())
+    Ok((changes, activity))
+}
+
+/// Fee charged and success of a transaction, from its base64 encoded
+/// `TransactionResultPair`
+pub(super) fn transaction_result(txresult: &str) -> Result<(i64, bool), IngestionError> {
+    let result = TransactionResultPair::from_xdr_base64(txresult, Limits::none())?;
+    let successful = matches!(
+        result.result.result,
+        TransactionResultResult::TxSuccess(_) | TransactionResultResult::TxFeeBumpInnerSuccess(_)
+    );
+    Ok((result.result.fee_charged, successful))
 }
 
-pub(super) async fn ingest_transaction(
-    db: &QuasarDatabase,
+async fn ingest_transaction(
+    db: &DatabaseTransaction,
     stellar_node_transaction: txhistory::Model,
+    closed_at: Option<DateTimeWithTimeZone>,
     metrics: &IngestionMetrics,
-) -> Result<(), IngestionError> {
+) -> Result<(Vec<LedgerChange>, transaction::Model), IngestionError> {
+    let (fee_charged, successful) = transaction_result(&stellar_node_transaction.txresult)?;
     let transaction_body =
         TransactionEnvelope::from_xdr_base64(&stellar_node_transaction.txbody, Limits::none())?;
     let transaction_meta =
         TransactionMeta::from_xdr_base64(&stellar_node_transaction.txmeta, Limits::none())?;
+    let changes = ledger_entry_changes(&transaction_meta)
+        .into_iter()
+        .map(|change| LedgerChange {
+            transaction_id: Some(stellar_node_transaction.txid.clone()),
+            change,
+        })
+        .collect();
     let mut transaction: transaction::ActiveModel =
         transaction::ActiveModel::try_from(transaction_body.clone())?;
 
     transaction.id = Set(stellar_node_transaction.txid.clone());
     transaction.application_order = Set(stellar_node_transaction.txindex);
     transaction.ledger_sequence = Set(stellar_node_transaction.ledgerseq);
-    transaction.insert(db.as_inner()).await?;
+    transaction.closed_at = Set(closed_at);
+    transaction.fee_charged = Set(Some(fee_charged));
+    transaction.successful = Set(Some(successful));
+    let transaction = transaction.insert(db).await?;
 
     ingest_operations(
         db,
         &stellar_node_transaction.txid,
+        closed_at,
         transaction_body,
         metrics,
     )
@@ -65,9 +123,10 @@ pub(super) async fn ingest_transaction(
         db,
         transaction_meta,
         &stellar_node_transaction.txid,
+        closed_at,
         metrics,
     )
     .await?;
 
-    Ok(())
+    Ok((changes, transaction))
 }
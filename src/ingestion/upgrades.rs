@@ -0,0 +1,36 @@
+use quasar_entities::upgrade;
+use sea_orm::{ColumnTrait, DatabaseTransaction, EntityTrait, QueryFilter, QueryOrder};
+use stellar_node_entities::{prelude::Upgradehistory, upgradehistory};
+
+use crate::databases::NodeDatabase;
+
+use super::{IngestionError, IngestionMetrics};
+
+pub(super) async fn ingest_upgrades(
+    node_database: &NodeDatabase,
+    db: &DatabaseTransaction,
+    ledger_sequence: i32,
+    metrics: &IngestionMetrics,
+) -> Result<(), IngestionError> {
+    let upgrades = Upgradehistory::find()
+        .filter(upgradehistory::Column::Ledgerseq.eq(ledger_sequence))
+        .order_by_asc(upgradehistory::Column::Upgradeindex)
+        .all(node_database.as_inner())
+        .await?;
+
+    if upgrades.is_empty() {
+        return Ok(());
+    }
+
+    let count = upgrades.len() as u64;
+    let upgrades = upgrades
+        .into_iter()
+        .map(upgrade::ActiveModel::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    upgrade::Entity::insert_many(upgrades).exec(db).await?;
+
+    metrics.upgrades.inc_by(count);
+
+    Ok(())
+}
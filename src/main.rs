@@ -13,23 +13,30 @@
     clippy::todo
 )]
 
-use clap::{command, Parser};
+use api_keys::{run_api_key_command, ApiKeyCommand};
+use clap::{command, Parser, Subcommand};
 use configuration::setup_configuration;
 use database_metrics::start_database_metrics;
 use databases::{setup_quasar_database, setup_stellar_node_database};
 use ingestion::ingest;
 use logger::setup_logger;
+use notifications::Notifier;
 use prometheus::Registry;
 use server::serve;
+use webhooks::{run_webhook_command, start_webhooks, WebhookCommand};
 
+mod api_keys;
 mod configuration;
 mod database_metrics;
 mod databases;
+mod horizon;
 mod ingestion;
 mod logger;
 mod metrics;
+mod notifications;
 mod schema;
 mod server;
+mod webhooks;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -41,20 +48,52 @@ struct Args {
     /// Stellar node URL to ingest data from
     #[arg(short, long)]
     stellar_node_database_url: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Manage the API keys of the clients
+    ApiKeys {
+        #[command(subcommand)]
+        command: ApiKeyCommand,
+    },
+    /// Manage the webhooks receiving contract events
+    Webhooks {
+        #[command(subcommand)]
+        command: WebhookCommand,
+    },
 }
 
 #[tokio::main]
 async fn main() {
-    let args = Args::parse();
+    let mut args = Args::parse();
+    let command = args.command.take();
 
     let configuration = setup_configuration(args);
 
     setup_logger();
 
     let quasar_database = setup_quasar_database(&configuration).await;
+
+    match command {
+        Some(Command::ApiKeys { command }) => {
+            run_api_key_command(&quasar_database, command).await;
+            return;
+        }
+        Some(Command::Webhooks { command }) => {
+            run_webhook_command(&quasar_database, &configuration.webhooks, command).await;
+            return;
+        }
+        None => {}
+    }
+
     let node_database = setup_stellar_node_database(&configuration).await;
 
     let metrics = Registry::new();
+    let notifier = Notifier::new();
 
     // Start a background task to collect database metrics
     start_database_metrics(
@@ -63,8 +102,23 @@ async fn main() {
         configuration.metrics.database_polling_interval,
     );
 
+    // Start a background task delivering events to webhooks
+    start_webhooks(
+        quasar_database.clone(),
+        configuration.webhooks,
+        metrics.clone(),
+        notifier.clone(),
+    );
+
     // Start the HTTP server, including GraphQL API
-    serve(&configuration.api, quasar_database.clone(), metrics.clone()).await;
+    serve(
+        &configuration.api,
+        &configuration.metrics,
+        quasar_database.clone(),
+        metrics.clone(),
+        notifier.clone(),
+    )
+    .await;
 
     // Start the ingestion loop
     ingest(
@@ -72,6 +126,7 @@ async fn main() {
         quasar_database,
         configuration.ingestion,
         metrics,
+        notifier,
     )
     .await;
 }
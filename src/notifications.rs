@@ -0,0 +1,111 @@
+use async_graphql::InputObject;
+use quasar_entities::{event, ledger, transaction};
+use sea_orm::{ColumnTrait, Condition};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+/// Notifications kept for subscribers, a subscriber lagging further behind
+/// misses the oldest ones
+const CAPACITY: usize = 1024;
+
+/// Data published by the ingestion loop once a ledger is fully ingested
+#[derive(Clone, Debug)]
+pub(crate) enum Notification {
+    Ledger(ledger::Model),
+    Transaction(transaction::Model),
+    Event(event::Model),
+}
+
+#[derive(Clone)]
+pub(crate) struct Notifier {
+    sender: Sender<Notification>,
+}
+
+impl Notifier {
+    pub(crate) fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    pub(crate) fn has_subscribers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    pub(crate) fn notify(&self, notification: Notification) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(notification);
+    }
+
+    pub(crate) fn subscribe(&self) -> Receiver<Notification> {
+        self.sender.subscribe()
+    }
+}
+
+/// Selects the contract events pushed to clients, by subscriptions, the event
+/// stream and webhooks, every field that is set must match
+#[derive(Clone, Debug, Default, Deserialize, InputObject)]
+#[graphql(name = "EventSubscriptionFilter")]
+pub(crate) struct EventPredicate {
+    #[serde(rename = "contract")]
+    pub(crate) contract_id: Option<String>,
+    pub(crate) topic: Option<String>,
+    pub(crate) r#type: Option<String>,
+}
+
+impl EventPredicate {
+    /// Whether a notified event matches
+    pub(crate) fn matches(&self, event: &event::Model) -> bool {
+        if let Some(contract_id) = &self.contract_id {
+            if &event.contract_id != contract_id {
+                return false;
+            }
+        }
+
+        if let Some(topic) = &self.topic {
+            if &event.topic != topic {
+                return false;
+            }
+        }
+
+        if let Some(r#type) = &self.r#type {
+            if &event.r#type != r#type {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Condition selecting the matching events from the database
+    pub(crate) fn condition(&self) -> Condition {
+        let mut condition = Condition::all();
+
+        if let Some(contract_id) = &self.contract_id {
+            condition = condition.add(event::Column::ContractId.eq(contract_id));
+        }
+
+        if let Some(topic) = &self.topic {
+            condition = condition.add(event::Column::Topic.eq(topic));
+        }
+
+        if let Some(r#type) = &self.r#type {
+            condition = condition.add(event::Column::Type.eq(r#type));
+        }
+
+        condition
+    }
+}
+
+/// JSON form of an event pushed to clients
+pub(crate) fn event_json(event: &event::Model) -> Value {
+    json!({
+        "id": event.id,
+        "contract_id": event.contract_id,
+        "transaction_id": event.transaction_id,
+        "topic": event.topic,
+        "type": event.r#type,
+        "value": event.value,
+        "closed_at": event.closed_at,
+    })
+}
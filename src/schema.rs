@@ -1,8 +1,8 @@
 use async_graphql::{
+    connection::{Connection, OpaqueCursor},
     dataloader::{DataLoader, Loader},
-    Context, EmptyMutation, EmptySubscription, Object, Result, Schema,
+    Context, EmptyMutation, Object, Result, Schema,
 };
-use log::info;
 use quasar_entities::account::AccountId;
 use quasar_entities::contract::ContractId;
 use quasar_entities::event::EventId;
@@ -10,30 +10,45 @@ use quasar_entities::operation::OperationId;
 use quasar_entities::transaction::TransactionId;
 use quasar_entities::{
     account::{self},
-    contract, event,
+    account_signer, contract, event,
+    filter::{
+        AccountFilter, ContractFilter, EventFilter, LedgerFilter, OperationFilter,
+        TransactionFilter, UpgradeFilter,
+    },
     ledger::{self, LedgerHash},
-    operation, transaction, QuasarDataLoader,
+    network_config, operation,
+    pagination::{paginate, Page, Pagination},
+    quorum_node, quorum_set,
+    sort::{
+        apply_sort, AccountSort, ContractSort, EventSort, LedgerSort, OperationSort,
+        TransactionSort, UpgradeSort,
+    },
+    transaction, upgrade, QuasarDataLoader,
+};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ColumnTrait, DatabaseConnection, EntityTrait, JoinType, Order,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait,
 };
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, Order, QueryFilter, QueryOrder};
 
-use crate::databases::QuasarDatabase;
+use crate::{
+    api_keys::{ApiKeys, ComplexityBudget},
+    databases::QuasarDatabase,
+    notifications::Notifier,
+};
 
 use self::{
-    filter::{
-        AccountFilter, ContractFilter, EventFilter, LedgerFilter, OperationFilter,
-        TransactionFilter,
-    },
-    pagination::{apply_pagination, Pagination},
-    sort::{AccountSort, ContractSort, EventSort, LedgerSort, OperationSort, TransactionSort},
+    connection::{keyset_connection, ConnectionArguments},
+    stats::{Bucket, EventCount, LedgerRange, OperationTypeCount, TimeRange, TransactionStats},
+    subscription::SubscriptionRoot,
 };
 
-pub(crate) type ServiceSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+pub(crate) type ServiceSchema = Schema<QueryRoot, EmptyMutation, SubscriptionRoot>;
 
 pub(crate) struct QueryRoot;
 
-mod filter;
-mod pagination;
-mod sort;
+mod connection;
+mod stats;
+mod subscription;
 
 #[Object]
 impl QueryRoot {
@@ -64,23 +79,97 @@ impl QueryRoot {
         &self,
         ctx: &Context<'_>,
         filter: Option<LedgerFilter>,
-        sort: Option<LedgerSort>,
+        sort: Option<Vec<LedgerSort>>,
         pagination: Option<Pagination>,
-    ) -> Result<Vec<ledger::Model>> {
+    ) -> Result<Page<ledger::Model>> {
         let database = ctx.data::<DatabaseConnection>().unwrap();
         let query = ledger::Entity::find();
-        let query = filter.map_or(query.clone(), |filter| filter.apply(query));
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
 
-        let (sort_column, sort_order) = match sort {
-            Some(LedgerSort::Sequence(order)) => (ledger::Column::Sequence, order.into()),
-            None => (ledger::Column::CreatedAt, Order::Desc),
+        let query = apply_sort(query, sort, &[(ledger::Column::Sequence, Order::Desc)]);
+
+        paginate(database, query, pagination).await
+    }
+
+    async fn ledgers_connection(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<LedgerFilter>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<OpaqueCursor<i32>, ledger::Model>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        let query = ledger::Entity::find();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+
+        keyset_connection(
+            database,
+            query,
+            ledger::Column::Sequence,
+            Order::Desc,
+            |ledger| ledger.sequence,
+            ConnectionArguments {
+                after,
+                before,
+                first,
+                last,
+            },
+        )
+        .await
+    }
+
+    async fn network_config(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "ledger sequence, defaults to the latest ingested ledger")]
+        at_ledger: Option<i32>,
+    ) -> Result<Option<network_config::Model>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        let query = network_config::Entity::find();
+        let query = match at_ledger {
+            Some(at_ledger) => query.filter(network_config::Column::LedgerSequence.lte(at_ledger)),
+            None => query,
         };
 
-        let mut query = query.order_by(sort_column, sort_order);
+        Ok(query
+            .order_by_desc(network_config::Column::LedgerSequence)
+            .one(database)
+            .await?)
+    }
 
-        query = apply_pagination(query, pagination);
+    async fn quorum_set(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "quorum set hash")] hash: String,
+    ) -> Result<Option<quorum_set::Model>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        Ok(quorum_set::Entity::find_by_id(hash).one(database).await?)
+    }
+
+    async fn quorum_sets(
+        &self,
+        ctx: &Context<'_>,
+        pagination: Option<Pagination>,
+    ) -> Result<Page<quorum_set::Model>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        let query = quorum_set::Entity::find()
+            .order_by_desc(quorum_set::Column::LastLedgerSequence)
+            .order_by_asc(quorum_set::Column::Hash);
 
-        Ok(query.all(database).await?)
+        paginate(database, query, pagination).await
+    }
+
+    async fn quorum_node(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "node public key")] node_id: String,
+    ) -> Result<Option<quorum_node::Model>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        Ok(quorum_node::Entity::find_by_id(node_id)
+            .one(database)
+            .await?)
     }
 
     async fn contract(
@@ -97,24 +186,46 @@ impl QueryRoot {
     async fn contracts(
         &self,
         ctx: &Context<'_>,
-        sort: Option<ContractSort>,
+        sort: Option<Vec<ContractSort>>,
         filter: Option<ContractFilter>,
         pagination: Option<Pagination>,
-    ) -> Result<Vec<contract::Model>> {
+    ) -> Result<Page<contract::Model>> {
         let database = ctx.data::<DatabaseConnection>().unwrap();
         let query = contract::Entity::find();
-        let query = filter.map_or(query.clone(), |filter| filter.apply(query));
-
-        let (sort_column, sort_order) = match sort {
-            Some(ContractSort::Address(order)) => (contract::Column::Address, order.into()),
-            None => (contract::Column::CreatedAt, Order::Desc),
-        };
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
 
-        let mut query = query.order_by(sort_column, sort_order);
+        let query = apply_sort(query, sort, &[(contract::Column::CreatedAt, Order::Desc)]);
 
-        query = apply_pagination(query, pagination);
+        paginate(database, query, pagination).await
+    }
 
-        Ok(query.all(database).await?)
+    async fn contracts_connection(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<ContractFilter>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<OpaqueCursor<String>, contract::Model>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        let query = contract::Entity::find();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+
+        keyset_connection(
+            database,
+            query,
+            contract::Column::Address,
+            Order::Asc,
+            |contract| contract.address.clone(),
+            ConnectionArguments {
+                after,
+                before,
+                first,
+                last,
+            },
+        )
+        .await
     }
 
     async fn account(
@@ -131,25 +242,63 @@ impl QueryRoot {
     async fn accounts(
         &self,
         ctx: &Context<'_>,
-        sort: Option<AccountSort>,
+        sort: Option<Vec<AccountSort>>,
         filter: Option<AccountFilter>,
         pagination: Option<Pagination>,
-    ) -> Result<Vec<account::Model>> {
+    ) -> Result<Page<account::Model>> {
         let database = ctx.data::<DatabaseConnection>().unwrap();
         let query = account::Entity::find();
-        let query = filter.map_or(query.clone(), |filter| filter.apply(query));
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
 
-        let (sort_column, sort_order) = match sort {
-            Some(AccountSort::Id(order)) => (account::Column::Id, order.into()),
-            Some(AccountSort::Balance(order)) => (account::Column::Balance, order.into()),
-            None => (account::Column::CreatedAt, Order::Desc),
-        };
+        let query = apply_sort(query, sort, &[(account::Column::CreatedAt, Order::Desc)]);
 
-        let mut query = query.order_by(sort_column, sort_order);
+        paginate(database, query, pagination).await
+    }
 
-        query = apply_pagination(query, pagination);
+    async fn accounts_connection(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<AccountFilter>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<OpaqueCursor<String>, account::Model>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        let query = account::Entity::find();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+
+        keyset_connection(
+            database,
+            query,
+            account::Column::Id,
+            Order::Asc,
+            |account| account.id.clone(),
+            ConnectionArguments {
+                after,
+                before,
+                first,
+                last,
+            },
+        )
+        .await
+    }
 
-        Ok(query.all(database).await?)
+    async fn accounts_by_signer(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "signer key")] signer: String,
+        pagination: Option<Pagination>,
+    ) -> Result<Page<account::Model>> {
+        let database = ctx.data::<DatabaseConnection>().unwrap();
+        let query = account::Entity::find()
+            .join(JoinType::InnerJoin, account::Relation::AccountSigner.def())
+            .filter(account_signer::Column::Signer.eq(signer))
+            // Merged accounts ingested before their signers were removed
+            .filter(account::Column::DeletedInLedger.is_null())
+            .order_by_asc(account::Column::Id);
+
+        paginate(database, query, pagination).await
     }
 
     async fn event(
@@ -166,25 +315,46 @@ impl QueryRoot {
     async fn events(
         &self,
         ctx: &Context<'_>,
-        sort: Option<EventSort>,
+        sort: Option<Vec<EventSort>>,
         filter: Option<EventFilter>,
         pagination: Option<Pagination>,
-    ) -> Result<Vec<event::Model>> {
+    ) -> Result<Page<event::Model>> {
         let database = ctx.data::<DatabaseConnection>().unwrap();
         let query = event::Entity::find();
-        let query = filter.map_or(query.clone(), |filter| filter.apply(query));
-
-        let (sort_column, sort_order) = match sort {
-            Some(EventSort::Id(order)) => (event::Column::Id, order.into()),
-            Some(EventSort::Type(order)) => (event::Column::Type, order.into()),
-            None => (event::Column::CreatedAt, Order::Desc),
-        };
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
 
-        let mut query = query.order_by(sort_column, sort_order);
+        let query = apply_sort(query, sort, &[(event::Column::CreatedAt, Order::Desc)]);
 
-        query = apply_pagination(query, pagination);
+        paginate(database, query, pagination).await
+    }
 
-        Ok(query.all(database).await?)
+    async fn events_connection(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<EventFilter>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<OpaqueCursor<i32>, event::Model>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        let query = event::Entity::find();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+
+        keyset_connection(
+            database,
+            query,
+            event::Column::Id,
+            Order::Desc,
+            |event| event.id,
+            ConnectionArguments {
+                after,
+                before,
+                first,
+                last,
+            },
+        )
+        .await
     }
 
     async fn transaction(
@@ -201,27 +371,53 @@ impl QueryRoot {
     async fn transactions(
         &self,
         ctx: &Context<'_>,
-        sort: Option<TransactionSort>,
+        sort: Option<Vec<TransactionSort>>,
         filter: Option<TransactionFilter>,
         pagination: Option<Pagination>,
-    ) -> Result<Vec<transaction::Model>> {
+    ) -> Result<Page<transaction::Model>> {
         let database = ctx.data::<DatabaseConnection>().unwrap();
         let query = transaction::Entity::find();
-        let query = filter.map_or(query.clone(), |filter| filter.apply(query));
-
-        let (sort_column, sort_order) = match sort {
-            Some(TransactionSort::Id(order)) => (transaction::Column::Id, order.into()),
-            Some(TransactionSort::LedgerSequence(order)) => {
-                (transaction::Column::LedgerSequence, order.into())
-            }
-            None => (transaction::Column::CreatedAt, Order::Desc),
-        };
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
 
-        let mut query = query.order_by(sort_column, sort_order);
+        let query = apply_sort(
+            query,
+            sort,
+            &[(transaction::Column::CreatedAt, Order::Desc)],
+        );
 
-        query = apply_pagination(query, pagination);
+        paginate(database, query, pagination).await
+    }
 
-        Ok(query.all(database).await?)
+    async fn transactions_connection(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<TransactionFilter>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<OpaqueCursor<(i32, i32)>, transaction::Model>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        let query = transaction::Entity::find();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+
+        keyset_connection(
+            database,
+            query,
+            (
+                transaction::Column::LedgerSequence,
+                transaction::Column::ApplicationOrder,
+            ),
+            Order::Desc,
+            |transaction| (transaction.ledger_sequence, transaction.application_order),
+            ConnectionArguments {
+                after,
+                before,
+                first,
+                last,
+            },
+        )
+        .await
     }
 
     async fn operation(
@@ -238,25 +434,113 @@ impl QueryRoot {
     async fn operations(
         &self,
         ctx: &Context<'_>,
-        sort: Option<OperationSort>,
+        sort: Option<Vec<OperationSort>>,
         filter: Option<OperationFilter>,
         pagination: Option<Pagination>,
-    ) -> Result<Vec<operation::Model>> {
+    ) -> Result<Page<operation::Model>> {
         let database = ctx.data::<DatabaseConnection>().unwrap();
         let query = operation::Entity::find();
-        let query = filter.map_or(query.clone(), |filter| filter.apply(query));
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
 
-        let (sort_column, sort_order) = match sort {
-            Some(OperationSort::Id(order)) => (operation::Column::Id, order.into()),
-            Some(OperationSort::Type(order)) => (operation::Column::Type, order.into()),
-            None => (operation::Column::CreatedAt, Order::Desc),
-        };
+        let query = apply_sort(query, sort, &[(operation::Column::CreatedAt, Order::Desc)]);
 
-        let mut query = query.order_by(sort_column, sort_order);
+        paginate(database, query, pagination).await
+    }
 
-        query = apply_pagination(query, pagination);
+    async fn operations_connection(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<OperationFilter>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<OpaqueCursor<i32>, operation::Model>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        let query = operation::Entity::find();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+
+        keyset_connection(
+            database,
+            query,
+            operation::Column::Id,
+            Order::Desc,
+            |operation| operation.id,
+            ConnectionArguments {
+                after,
+                before,
+                first,
+                last,
+            },
+        )
+        .await
+    }
+
+    async fn upgrades(
+        &self,
+        ctx: &Context<'_>,
+        sort: Option<Vec<UpgradeSort>>,
+        filter: Option<UpgradeFilter>,
+        pagination: Option<Pagination>,
+    ) -> Result<Page<upgrade::Model>> {
+        let database = ctx.data::<DatabaseConnection>().unwrap();
+        let query = upgrade::Entity::find();
+        let query = filter.map_or(Ok(query.clone()), |filter| filter.apply(query))?;
+
+        let query = apply_sort(
+            query,
+            sort,
+            &[
+                (upgrade::Column::LedgerSequence, Order::Desc),
+                (upgrade::Column::UpgradeIndex, Order::Desc),
+            ],
+        );
+
+        paginate(database, query, pagination).await
+    }
+
+    /// Transaction counts, fee sums and success rates per time bucket
+    async fn transaction_stats(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "inclusive start of the series, 1000 buckets before the end by default")]
+        from: Option<DateTimeWithTimeZone>,
+        #[graphql(desc = "exclusive end of the series, now by default")] to: Option<
+            DateTimeWithTimeZone,
+        >,
+        bucket: Bucket,
+    ) -> Result<Vec<TransactionStats>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        let range = TimeRange { from, to }.bounded(bucket)?;
+        Ok(stats::transaction_stats(database, range, bucket).await?)
+    }
 
-        Ok(query.all(database).await?)
+    /// Number of contract events per time bucket
+    async fn event_counts(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "contract address")] contract: Option<String>,
+        topic: Option<String>,
+        #[graphql(desc = "inclusive start of the series, 1000 buckets before the end by default")]
+        from: Option<DateTimeWithTimeZone>,
+        #[graphql(desc = "exclusive end of the series, now by default")] to: Option<
+            DateTimeWithTimeZone,
+        >,
+        bucket: Bucket,
+    ) -> Result<Vec<EventCount>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        let range = TimeRange { from, to }.bounded(bucket)?;
+        Ok(stats::event_counts(database, contract, topic, range, bucket).await?)
+    }
+
+    /// Number of operations of every type, most frequent first
+    async fn operation_type_distribution(
+        &self,
+        ctx: &Context<'_>,
+        ledger_range: Option<LedgerRange>,
+    ) -> Result<Vec<OperationTypeCount>> {
+        let database = ctx.data::<DatabaseConnection>()?;
+        Ok(stats::operation_type_distribution(database, ledger_range).await?)
     }
 }
 
@@ -264,20 +548,18 @@ pub(super) fn build_schema(
     depth_limit: usize,
     complexity_limit: usize,
     database: QuasarDatabase,
+    notifier: Notifier,
+    api_keys: ApiKeys,
 ) -> ServiceSchema {
     let database = database.as_inner().clone();
     let quasar_db = QuasarDataLoader::new(database.clone());
-    let mut schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+    Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
         .data(DataLoader::new(quasar_db.clone(), tokio::spawn))
         .data(database)
-        .data(quasar_db);
-
-    if cfg!(debug_assertions) {
-        info!("Debugging enabled, no limits on query");
-    } else {
-        schema = schema.limit_depth(depth_limit);
-        schema = schema.limit_complexity(complexity_limit);
-    }
-
-    schema.finish()
+        .data(quasar_db)
+        .data(notifier)
+        .extension(ComplexityBudget(api_keys))
+        .limit_depth(depth_limit)
+        .limit_complexity(complexity_limit)
+        .finish()
 }
@@ -0,0 +1,106 @@
+use async_graphql::{
+    connection::{query, Connection, Edge, OpaqueCursor},
+    OutputType, Result,
+};
+use sea_orm::{
+    sea_query::IntoValueTuple, DatabaseConnection, EntityTrait, IntoIdentity, Order, Select,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Rows returned when neither `first` nor `last` is given
+const DEFAULT_PAGE_SIZE: usize = 100;
+/// Rows returned at most, larger `first` and `last` values are clamped
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Relay `first`/`after`/`last`/`before` arguments
+pub(super) struct ConnectionArguments {
+    pub(super) after: Option<String>,
+    pub(super) before: Option<String>,
+    pub(super) first: Option<i32>,
+    pub(super) last: Option<i32>,
+}
+
+/// Builds a Relay connection with keyset pagination on the given key columns,
+/// cursors encode the key of the row so pages stay stable while rows are inserted
+pub(super) async fn keyset_connection<E, C, K, F>(
+    database: &DatabaseConnection,
+    select: Select<E>,
+    key_columns: C,
+    order: Order,
+    key: F,
+    arguments: ConnectionArguments,
+) -> Result<Connection<OpaqueCursor<K>, E::Model>>
+where
+    E: EntityTrait,
+    E::Model: OutputType + Sync,
+    C: IntoIdentity,
+    K: IntoValueTuple + Serialize + DeserializeOwned + Send + Sync,
+    F: Fn(&E::Model) -> K,
+{
+    let ConnectionArguments {
+        after,
+        before,
+        first,
+        last,
+    } = arguments;
+
+    query(
+        after,
+        before,
+        first,
+        last,
+        |after: Option<OpaqueCursor<K>>, before: Option<OpaqueCursor<K>>, first, last| async move {
+            let has_after = after.is_some();
+            let has_before = before.is_some();
+
+            let mut cursor = select.cursor_by(key_columns);
+            match order {
+                Order::Desc => cursor.desc(),
+                _ => cursor.asc(),
+            };
+
+            if let Some(OpaqueCursor(after)) = after {
+                cursor.after(after);
+            }
+            if let Some(OpaqueCursor(before)) = before {
+                cursor.before(before);
+            }
+
+            // One extra row is fetched to know whether another page follows
+            let backward = first.is_none() && last.is_some();
+            let limit = first
+                .or(last)
+                .unwrap_or(DEFAULT_PAGE_SIZE)
+                .min(MAX_PAGE_SIZE);
+            if backward {
+                cursor.last(limit as u64 + 1);
+            } else {
+                cursor.first(limit as u64 + 1);
+            }
+
+            let mut rows = cursor.all(database).await?;
+            let has_more = rows.len() > limit;
+
+            let (has_previous_page, has_next_page) = if backward {
+                if has_more {
+                    rows.remove(0);
+                }
+                (has_more, has_before)
+            } else {
+                if has_more {
+                    rows.pop();
+                }
+                (has_after, has_more)
+            };
+
+            let mut connection = Connection::new(has_previous_page, has_next_page);
+            connection.edges.extend(
+                rows.into_iter()
+                    .map(|row| Edge::new(OpaqueCursor(key(&row)), row)),
+            );
+
+            Ok::<_, async_graphql::Error>(connection)
+        },
+    )
+    .await
+}
@@ -0,0 +1,225 @@
+use async_graphql::{Enum, Error, InputObject, SimpleObject};
+use chrono::{Duration, Utc};
+use quasar_entities::{event, operation, transaction};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone,
+    sea_query::{Alias, Expr, SimpleExpr},
+    ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, FromQueryResult, JoinType,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+};
+
+/// Buckets a series spans at most
+const MAX_BUCKETS: i32 = 1000;
+
+/// Width of the time buckets of a statistics series
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub(super) enum Bucket {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl Bucket {
+    /// Longest duration of a bucket
+    fn width(self) -> Duration {
+        match self {
+            Self::Minute => Duration::minutes(1),
+            Self::Hour => Duration::hours(1),
+            Self::Day => Duration::days(1),
+            Self::Week => Duration::weeks(1),
+            Self::Month => Duration::days(31),
+        }
+    }
+
+    /// Close time of the rows of the table truncated to the start of their bucket
+    fn truncate(self, table: &str) -> SimpleExpr {
+        let unit = match self {
+            Self::Minute => "minute",
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        };
+        Expr::cust(format!(
+            "date_trunc('{}', \"{}\".\"closed_at\")",
+            unit, table
+        ))
+    }
+}
+
+/// Inclusive start and exclusive end of a series, either bound can be left open
+pub(super) struct TimeRange {
+    pub from: Option<DateTimeWithTimeZone>,
+    pub to: Option<DateTimeWithTimeZone>,
+}
+
+impl TimeRange {
+    /// Closes the open bounds of the range, the end at the current time and the
+    /// start `MAX_BUCKETS` buckets before the end, and rejects ranges spanning
+    /// more buckets
+    pub fn bounded(self, bucket: Bucket) -> Result<Self, Error> {
+        let to = self.to.unwrap_or_else(|| Utc::now().into());
+        let span = bucket.width() * MAX_BUCKETS;
+        let from = self.from.unwrap_or(to - span);
+
+        if to - from > span {
+            return Err(Error::new(format!(
+                "The series spans more than {} buckets, narrow the range or widen the bucket",
+                MAX_BUCKETS
+            )));
+        }
+
+        Ok(Self {
+            from: Some(from),
+            to: Some(to),
+        })
+    }
+
+    fn condition<C: ColumnTrait>(&self, column: C) -> Condition {
+        let mut condition = Condition::all();
+
+        if let Some(from) = self.from {
+            condition = condition.add(column.gte(from));
+        }
+
+        if let Some(to) = self.to {
+            condition = condition.add(column.lt(to));
+        }
+
+        condition
+    }
+}
+
+/// Inclusive range of ledger sequences
+#[derive(InputObject)]
+pub(super) struct LedgerRange {
+    from: i32,
+    to: i32,
+}
+
+#[derive(SimpleObject, FromQueryResult)]
+pub(super) struct TransactionStats {
+    /// Start of the bucket
+    bucket: DateTimeWithTimeZone,
+    transaction_count: i64,
+    successful_transaction_count: i64,
+    failed_transaction_count: i64,
+    operation_count: i64,
+    /// Sum of the fees charged, in stroops
+    fee_charged: i64,
+    /// Share of successful transactions, `None` when the result of no
+    /// transaction of the bucket is known
+    success_rate: Option<f64>,
+}
+
+#[derive(SimpleObject, FromQueryResult)]
+pub(super) struct EventCount {
+    /// Start of the bucket
+    bucket: DateTimeWithTimeZone,
+    count: i64,
+}
+
+#[derive(SimpleObject, FromQueryResult)]
+pub(super) struct OperationTypeCount {
+    r#type: String,
+    count: i64,
+}
+
+/// Buckets without any row are left out of the series
+pub(super) async fn transaction_stats(
+    database: &DatabaseConnection,
+    range: TimeRange,
+    bucket: Bucket,
+) -> Result<Vec<TransactionStats>, DbErr> {
+    transaction::Entity::find()
+        .select_only()
+        .column_as(bucket.truncate("transactions"), "bucket")
+        .column_as(Expr::cust("count(*)"), "transaction_count")
+        .column_as(
+            Expr::cust("count(*) FILTER (WHERE \"transactions\".\"successful\")"),
+            "successful_transaction_count",
+        )
+        .column_as(
+            Expr::cust("count(*) FILTER (WHERE NOT \"transactions\".\"successful\")"),
+            "failed_transaction_count",
+        )
+        .column_as(
+            Expr::cust("coalesce(sum(\"transactions\".\"operation_count\"), 0)::bigint"),
+            "operation_count",
+        )
+        .column_as(
+            Expr::cust("coalesce(sum(\"transactions\".\"fee_charged\"), 0)::bigint"),
+            "fee_charged",
+        )
+        .column_as(
+            Expr::cust(
+                "(count(*) FILTER (WHERE \"transactions\".\"successful\"))::float8 \
+                 / nullif(count(\"transactions\".\"successful\"), 0)",
+            ),
+            "success_rate",
+        )
+        .filter(transaction::Column::ClosedAt.is_not_null())
+        .filter(range.condition(transaction::Column::ClosedAt))
+        .group_by(Expr::col(Alias::new("bucket")))
+        .order_by_asc(Expr::col(Alias::new("bucket")))
+        .into_model::<TransactionStats>()
+        .all(database)
+        .await
+}
+
+pub(super) async fn event_counts(
+    database: &DatabaseConnection,
+    contract: Option<String>,
+    topic: Option<String>,
+    range: TimeRange,
+    bucket: Bucket,
+) -> Result<Vec<EventCount>, DbErr> {
+    let mut query = event::Entity::find()
+        .select_only()
+        .column_as(bucket.truncate("events"), "bucket")
+        .column_as(Expr::cust("count(*)"), "count")
+        .filter(event::Column::ClosedAt.is_not_null())
+        .filter(range.condition(event::Column::ClosedAt));
+
+    if let Some(contract) = contract {
+        query = query.filter(event::Column::ContractId.eq(contract));
+    }
+
+    if let Some(topic) = topic {
+        query = query.filter(event::Column::Topic.eq(topic));
+    }
+
+    query
+        .group_by(Expr::col(Alias::new("bucket")))
+        .order_by_asc(Expr::col(Alias::new("bucket")))
+        .into_model::<EventCount>()
+        .all(database)
+        .await
+}
+
+/// Number of operations of every type, most frequent first
+pub(super) async fn operation_type_distribution(
+    database: &DatabaseConnection,
+    ledger_range: Option<LedgerRange>,
+) -> Result<Vec<OperationTypeCount>, DbErr> {
+    let mut query = operation::Entity::find()
+        .select_only()
+        .column(operation::Column::Type)
+        .column_as(Expr::cust("count(*)"), "count");
+
+    if let Some(LedgerRange { from, to }) = ledger_range {
+        query = query
+            .join(JoinType::InnerJoin, operation::Relation::Transaction.def())
+            .filter(transaction::Column::LedgerSequence.between(from, to));
+    }
+
+    query
+        .group_by(operation::Column::Type)
+        .order_by_desc(Expr::col(Alias::new("count")))
+        .order_by_asc(operation::Column::Type)
+        .into_model::<OperationTypeCount>()
+        .all(database)
+        .await
+}
@@ -0,0 +1,76 @@
+use async_graphql::{Context, InputObject, Result, Subscription};
+use quasar_entities::{event, ledger, transaction};
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+
+use crate::notifications::{EventPredicate, Notification, Notifier};
+
+pub(crate) struct SubscriptionRoot;
+
+#[derive(InputObject)]
+pub(super) struct TransactionSubscriptionFilter {
+    pub(super) account_id: Option<String>,
+}
+
+impl TransactionSubscriptionFilter {
+    fn matches(&self, transaction: &transaction::Model) -> bool {
+        if let Some(account_id) = &self.account_id {
+            if &transaction.account_id != account_id {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+fn notifications(ctx: &Context<'_>) -> Result<impl Stream<Item = Notification>> {
+    let notifier = ctx.data::<Notifier>()?;
+    // The stream ends when the subscriber lags behind and misses notifications,
+    // rather than silently skipping them, so that clients resubscribe and
+    // catch up from the queries
+    Ok(BroadcastStream::new(notifier.subscribe()).map_while(Result::ok))
+}
+
+#[Subscription]
+impl SubscriptionRoot {
+    async fn new_ledger(&self, ctx: &Context<'_>) -> Result<impl Stream<Item = ledger::Model>> {
+        Ok(
+            notifications(ctx)?.filter_map(|notification| match notification {
+                Notification::Ledger(ledger) => Some(ledger),
+                _ => None,
+            }),
+        )
+    }
+
+    async fn new_transactions(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<TransactionSubscriptionFilter>,
+    ) -> Result<impl Stream<Item = transaction::Model>> {
+        Ok(
+            notifications(ctx)?.filter_map(move |notification| match notification {
+                Notification::Transaction(transaction) => match &filter {
+                    Some(filter) if !filter.matches(&transaction) => None,
+                    _ => Some(transaction),
+                },
+                _ => None,
+            }),
+        )
+    }
+
+    async fn contract_events(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<EventPredicate>,
+    ) -> Result<impl Stream<Item = event::Model>> {
+        Ok(
+            notifications(ctx)?.filter_map(move |notification| match notification {
+                Notification::Event(event) => match &filter {
+                    Some(filter) if !filter.matches(&event) => None,
+                    _ => Some(event),
+                },
+                _ => None,
+            }),
+        )
+    }
+}
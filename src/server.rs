@@ -1,41 +1,143 @@
-use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
-use async_graphql_axum::GraphQL;
+use async_graphql::http::{playground_source, GraphQLPlaygroundConfig, ALL_WEBSOCKET_PROTOCOLS};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
 use axum::{
-    response::{Html, IntoResponse},
-    routing::get,
-    Router,
+    extract::{ws::WebSocketUpgrade, State},
+    middleware,
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+    Extension, Router,
 };
 use axum_prometheus::PrometheusMetricLayer;
 use log::info;
 use prometheus::Registry;
 
+use quasar_entities::api_key;
+
 use crate::{
-    configuration::Api, databases::QuasarDatabase, metrics::collect_metrics, schema::build_schema,
+    api_keys::{authenticate, ApiKeys, KeySource},
+    configuration::{Api, Metrics},
+    databases::QuasarDatabase,
+    horizon,
+    metrics::collect_metrics,
+    notifications::Notifier,
+    schema::{build_schema, ServiceSchema},
+};
+
+use self::{
+    events::{stream_events, EventStreamState},
+    rpc::handle_rpc,
 };
 
+mod events;
+mod rpc;
+
 pub(crate) async fn graphql_playground() -> impl IntoResponse {
     Html(playground_source(
         GraphQLPlaygroundConfig::new("/").subscription_endpoint("/ws"),
     ))
 }
 
-pub(super) async fn serve(api: &Api, database: QuasarDatabase, metrics: Registry) {
-    let schema = build_schema(api.depth_limit, api.complexity_limit, database);
+/// Executes a GraphQL request with the API key of the client, if any
+async fn graphql_handler(
+    State(schema): State<ServiceSchema>,
+    api_key: Option<Extension<api_key::Model>>,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    let mut request = request.into_inner();
+    if let Some(Extension(api_key)) = api_key {
+        request = request.data(api_key);
+    }
+    schema.execute(request).await.into()
+}
+
+/// Serves GraphQL subscriptions, clients which cannot set headers on the
+/// upgrade request send their API key as `apiKey` in the `connection_init`
+/// payload
+async fn graphql_ws_handler(
+    State((schema, api_keys)): State<(ServiceSchema, ApiKeys)>,
+    api_key: Option<Extension<api_key::Model>>,
+    protocol: GraphQLProtocol,
+    websocket: WebSocketUpgrade,
+) -> Response {
+    let api_key = api_key.map(|Extension(api_key)| api_key);
+
+    websocket
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |stream| {
+            GraphQLWebSocket::new(stream, schema, protocol)
+                .on_connection_init(move |payload| async move {
+                    api_keys.connection_data(api_key, payload).await
+                })
+                .serve()
+        })
+}
+
+pub(super) async fn serve(
+    api: &Api,
+    metrics_configuration: &Metrics,
+    database: QuasarDatabase,
+    metrics: Registry,
+    notifier: Notifier,
+) {
+    let rpc_database = database.as_inner().clone();
+    let horizon = horizon::router(database.as_inner().clone());
+    let event_stream_state = EventStreamState {
+        database: database.as_inner().clone(),
+        notifier: notifier.clone(),
+    };
+    let api_keys = ApiKeys::new(&database, api, &metrics);
+    let schema = build_schema(
+        api.depth_limit,
+        api.complexity_limit,
+        database,
+        notifier,
+        api_keys.clone(),
+    );
 
     let (prometheus_layer, metric_handle) = PrometheusMetricLayer::pair();
 
-    let app = Router::new()
+    // Every route serving data goes through the API key checks, the key is
+    // only accepted in the query string by the streaming routes
+    let api_routes = Router::new()
+        .route("/", post(graphql_handler).with_state(schema.clone()))
+        .route("/rpc", post(handle_rpc).with_state(rpc_database))
+        .merge(horizon)
+        .route_layer(middleware::from_fn_with_state(
+            (api_keys.clone(), KeySource::Headers),
+            authenticate,
+        ));
+    let event_stream_routes = Router::new()
         .route(
-            "/",
-            get(graphql_playground).post_service(GraphQL::new(schema)),
+            "/events/stream",
+            get(stream_events).with_state(event_stream_state),
         )
+        .route_layer(middleware::from_fn_with_state(
+            (api_keys.clone(), KeySource::HeadersOrQuery),
+            authenticate,
+        ));
+    let subscription_routes = Router::new()
         .route(
-            "/metrics",
-            get(|| async move { collect_metrics(metrics, metric_handle) }),
+            "/ws",
+            get(graphql_ws_handler).with_state((schema, api_keys.clone())),
         )
+        .route_layer(middleware::from_fn_with_state(
+            (api_keys, KeySource::WebSocket),
+            authenticate,
+        ));
+
+    let app = Router::new()
+        .route("/", get(graphql_playground))
+        .merge(api_routes)
+        .merge(event_stream_routes)
+        .merge(subscription_routes)
         .layer(prometheus_layer);
+    let metrics_app = Router::new().route(
+        "/metrics",
+        get(|| async move { collect_metrics(metrics, metric_handle) }),
+    );
 
     let socket_addr = (api.host, api.port).into();
+    let metrics_socket_addr = (metrics_configuration.host, metrics_configuration.port).into();
 
     tokio::spawn(async move {
         axum::Server::bind(&socket_addr)
@@ -43,6 +145,13 @@ pub(super) async fn serve(api: &Api, database: QuasarDatabase, metrics: Registry
             .await
             .unwrap();
     });
+    tokio::spawn(async move {
+        axum::Server::bind(&metrics_socket_addr)
+            .serve(metrics_app.into_make_service())
+            .await
+            .unwrap();
+    });
 
     info!("API started on {}", socket_addr);
+    info!("Metrics served on {}", metrics_socket_addr);
 }
@@ -0,0 +1,131 @@
+use std::{collections::VecDeque, convert::Infallible};
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event as SseEvent, KeepAlive, Sse},
+};
+use futures::{stream, Stream};
+use log::error;
+use quasar_entities::event;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use crate::notifications::{event_json, EventPredicate, Notification, Notifier};
+
+/// Events fetched at once when replaying from the database
+const PAGE_SIZE: u64 = 100;
+
+#[derive(Clone)]
+pub(super) struct EventStreamState {
+    pub(super) database: DatabaseConnection,
+    pub(super) notifier: Notifier,
+}
+
+struct EventStream {
+    database: DatabaseConnection,
+    predicate: EventPredicate,
+    notifications: Receiver<Notification>,
+    /// Id of the last event sent to the client
+    last_id: Option<i32>,
+    /// Whether events up to the latest ingested ledger were sent
+    caught_up: bool,
+    pending: VecDeque<event::Model>,
+}
+
+impl EventStream {
+    async fn next(&mut self) -> Option<event::Model> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                self.last_id = Some(event.id);
+                return Some(event);
+            }
+
+            if !self.caught_up {
+                match self.replay().await {
+                    Ok(events) if events.is_empty() => self.caught_up = true,
+                    Ok(events) => self.pending.extend(events),
+                    Err(err) => {
+                        error!("Failed to replay events: {}", err);
+                        return None;
+                    }
+                }
+                continue;
+            }
+
+            match self.notifications.recv().await {
+                Ok(Notification::Event(event)) if self.is_new(&event) => {
+                    self.pending.push_back(event)
+                }
+                Ok(_) => {}
+                // Catch up on the missed events from the database
+                Err(RecvError::Lagged(_)) => self.caught_up = false,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    fn is_new(&self, event: &event::Model) -> bool {
+        if let Some(last_id) = self.last_id {
+            if event.id <= last_id {
+                return false;
+            }
+        }
+
+        self.predicate.matches(event)
+    }
+
+    async fn replay(&self) -> Result<Vec<event::Model>, sea_orm::DbErr> {
+        let mut query = event::Entity::find().filter(self.predicate.condition());
+
+        if let Some(last_id) = self.last_id {
+            query = query.filter(event::Column::Id.gt(last_id));
+        }
+
+        query
+            .order_by_asc(event::Column::Id)
+            .limit(PAGE_SIZE)
+            .all(&self.database)
+            .await
+    }
+}
+
+fn to_sse(event: event::Model) -> SseEvent {
+    SseEvent::default()
+        .id(event.id.to_string())
+        .event("contract_event")
+        .data(event_json(&event).to_string())
+}
+
+/// Streams newly ingested contract events, clients reconnecting with a
+/// `Last-Event-ID` header first receive the events they missed
+pub(super) async fn stream_events(
+    State(state): State<EventStreamState>,
+    Query(predicate): Query<EventPredicate>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, StatusCode> {
+    let last_id = match headers.get("last-event-id") {
+        Some(value) => {
+            let value = value.to_str().map_err(|_| StatusCode::BAD_REQUEST)?;
+            Some(value.parse().map_err(|_| StatusCode::BAD_REQUEST)?)
+        }
+        None => None,
+    };
+
+    // Subscribe before replaying so no event is lost in between
+    let event_stream = EventStream {
+        database: state.database,
+        predicate,
+        notifications: state.notifier.subscribe(),
+        last_id,
+        caught_up: last_id.is_none(),
+        pending: VecDeque::new(),
+    };
+
+    let stream = stream::unfold(event_stream, |mut event_stream| async move {
+        let event = event_stream.next().await?;
+        Some((Ok(to_sse(event)), event_stream))
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,483 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::{extract::State, Json};
+use log::error;
+use quasar_entities::{backfill, event, ledger, transaction};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait,
+    IntoSimpleExpr, JoinType, QueryFilter, QueryOrder, QuerySelect, RelationTrait,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::horizon::{operation_toid, parse_operation_toid};
+
+/// Events returned when the request does not set a limit
+const DEFAULT_LIMIT: u64 = 100;
+const MAX_LIMIT: u64 = 10_000;
+/// Limits of the Soroban RPC `getEvents` filters
+const MAX_FILTERS: usize = 5;
+const MAX_CONTRACT_IDS: usize = 5;
+const MAX_TOPIC_FILTERS: usize = 5;
+const MAX_TOPIC_SEGMENTS: usize = 4;
+/// Topic segment matching any value
+const WILDCARD: &str = "*";
+/// Backfill storing the XDR of the events ingested before it was stored
+const EVENT_XDR_BACKFILL: &str = "event_xdr";
+
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const INTERNAL_ERROR: i32 = -32603;
+
+#[derive(Deserialize)]
+pub(super) struct RpcRequest {
+    jsonrpc: String,
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+struct RpcError {
+    code: i32,
+    message: String,
+}
+
+impl RpcError {
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<DbErr> for RpcError {
+    fn from(err: DbErr) -> Self {
+        error!("Failed to serve JSON-RPC request: {}", err);
+        Self {
+            code: INTERNAL_ERROR,
+            message: "internal error".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetEventsParams {
+    start_ledger: Option<i32>,
+    #[serde(default)]
+    filters: Vec<EventFilter>,
+    pagination: Option<PaginationOptions>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventFilter {
+    r#type: Option<String>,
+    #[serde(default)]
+    contract_ids: Vec<String>,
+    #[serde(default)]
+    topics: Vec<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct PaginationOptions {
+    cursor: Option<String>,
+    limit: Option<u64>,
+}
+
+impl EventFilter {
+    fn validate(&self) -> Result<(), RpcError> {
+        if let Some(r#type) = &self.r#type {
+            if !matches!(r#type.as_str(), "contract" | "system" | "diagnostic") {
+                return Err(RpcError::invalid_params(format!(
+                    "invalid event type: {}",
+                    r#type
+                )));
+            }
+        }
+
+        if self.contract_ids.len() > MAX_CONTRACT_IDS {
+            return Err(RpcError::invalid_params(format!(
+                "maximum {} contract ids per filter",
+                MAX_CONTRACT_IDS
+            )));
+        }
+
+        for contract_id in &self.contract_ids {
+            if stellar_strkey::Contract::from_string(contract_id).is_err() {
+                return Err(RpcError::invalid_params(format!(
+                    "invalid contract id: {}",
+                    contract_id
+                )));
+            }
+        }
+
+        if self.topics.len() > MAX_TOPIC_FILTERS {
+            return Err(RpcError::invalid_params(format!(
+                "maximum {} topic filters per filter",
+                MAX_TOPIC_FILTERS
+            )));
+        }
+
+        for segments in &self.topics {
+            if segments.is_empty() || segments.len() > MAX_TOPIC_SEGMENTS {
+                return Err(RpcError::invalid_params(format!(
+                    "topic filters must have 1 to {} segments",
+                    MAX_TOPIC_SEGMENTS
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn condition(&self) -> Condition {
+        let mut condition = Condition::all();
+
+        // Event types are stored as their XDR names, e.g. "Contract"
+        if let Some(r#type) = &self.r#type {
+            condition = condition.add(Expr::cust_with_values(
+                "lower(\"events\".\"type\") = $1",
+                [r#type.clone()],
+            ));
+        }
+
+        if !self.contract_ids.is_empty() {
+            condition = condition.add(event::Column::ContractId.is_in(self.contract_ids.clone()));
+        }
+
+        if !self.topics.is_empty() {
+            let mut topics = Condition::any();
+
+            for segments in &self.topics {
+                let mut topic = Condition::all().add(Expr::cust_with_values(
+                    "json_array_length(\"events\".\"raw_topics\") = $1",
+                    [segments.len() as i32],
+                ));
+
+                for (index, segment) in segments.iter().enumerate() {
+                    if segment != WILDCARD {
+                        topic = topic.add(Expr::cust_with_values(
+                            "\"events\".\"raw_topics\" ->> $1 = $2",
+                            [
+                                sea_orm::Value::from(index as i32),
+                                sea_orm::Value::from(segment.clone()),
+                            ],
+                        ));
+                    }
+                }
+
+                topics = topics.add(topic);
+            }
+
+            condition = condition.add(topics);
+        }
+
+        condition
+    }
+}
+
+/// JSON-RPC endpoint implementing the Soroban RPC methods backed by Quasar
+pub(super) async fn handle_rpc(
+    State(database): State<DatabaseConnection>,
+    Json(request): Json<RpcRequest>,
+) -> Json<Value> {
+    let result = if request.jsonrpc != "2.0" {
+        Err(RpcError {
+            code: INVALID_REQUEST,
+            message: "jsonrpc must be \"2.0\"".to_string(),
+        })
+    } else {
+        match request.method.as_str() {
+            "getEvents" => get_events(&database, request.params).await,
+            method => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("method not found: {}", method),
+            }),
+        }
+    };
+
+    let response = match result {
+        Ok(result) => json!({
+            "jsonrpc": "2.0",
+            "id": request.id,
+            "result": result,
+        }),
+        Err(err) => json!({
+            "jsonrpc": "2.0",
+            "id": request.id,
+            "error": {
+                "code": err.code,
+                "message": err.message,
+            },
+        }),
+    };
+
+    Json(response)
+}
+
+async fn get_events(database: &DatabaseConnection, params: Value) -> Result<Value, RpcError> {
+    let params: GetEventsParams =
+        serde_json::from_value(params).map_err(|err| RpcError::invalid_params(err.to_string()))?;
+
+    if params.filters.len() > MAX_FILTERS {
+        return Err(RpcError::invalid_params(format!(
+            "maximum {} filters per request",
+            MAX_FILTERS
+        )));
+    }
+    for filter in &params.filters {
+        filter.validate()?;
+    }
+
+    let (cursor, limit) = match params.pagination {
+        Some(pagination) => (pagination.cursor, pagination.limit),
+        None => (None, None),
+    };
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(RpcError::invalid_params(format!(
+            "limit must be between 1 and {}",
+            MAX_LIMIT
+        )));
+    }
+
+    // Events ingested before their XDR was stored cannot be served until the
+    // backfill reaches them, ranges starting before the end of the backfill
+    // are rejected while it runs
+    let pending_ledger = pending_xdr_ledger(database).await?;
+    let pending_xdr = |ledger_sequence: i32| match pending_ledger {
+        Some(pending_ledger) if i64::from(ledger_sequence) <= pending_ledger => {
+            Err(RpcError::invalid_params(format!(
+                "events up to ledger {} missing XDR, start after this ledger",
+                pending_ledger
+            )))
+        }
+        _ => Ok(()),
+    };
+
+    // Events whose XDR the node could not provide are skipped
+    let mut query = event::Entity::find()
+        .find_also_related(transaction::Entity)
+        .filter(event::Column::RawValue.is_not_null());
+
+    match (cursor, params.start_ledger) {
+        (Some(_), Some(_)) => {
+            return Err(RpcError::invalid_params(
+                "startLedger and cursor cannot both be set",
+            ))
+        }
+        (Some(cursor), None) => {
+            let cursor = parse_event_id(&cursor)
+                .ok_or_else(|| RpcError::invalid_params("invalid cursor"))?;
+            pending_xdr(cursor.0)?;
+            query = query.filter(after_event(database, cursor).await?);
+        }
+        (None, Some(start_ledger)) => {
+            pending_xdr(start_ledger)?;
+            query = query.filter(transaction::Column::LedgerSequence.gte(start_ledger));
+        }
+        (None, None) => {
+            return Err(RpcError::invalid_params(
+                "either startLedger or cursor must be set",
+            ))
+        }
+    }
+
+    if !params.filters.is_empty() {
+        let mut filters = Condition::any();
+        for filter in &params.filters {
+            filters = filters.add(filter.condition());
+        }
+        query = query.filter(filters);
+    }
+
+    let events = query
+        .order_by_asc(event::Column::Id)
+        .limit(limit)
+        .all(database)
+        .await?;
+
+    let latest_ledger = ledger::Entity::find()
+        .order_by_desc(ledger::Column::Sequence)
+        .one(database)
+        .await?
+        .map(|ledger| ledger.sequence);
+
+    let indexes = event_indexes(database, &events).await?;
+    let events = events
+        .into_iter()
+        .map(|(event, transaction)| {
+            let index = indexes.get(&event.id).copied();
+            event_info(event, transaction, index)
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "events": events,
+        "latestLedger": latest_ledger,
+    }))
+}
+
+/// Last ledger whose events may still miss their XDR, while the backfill
+/// storing it runs
+async fn pending_xdr_ledger(database: &DatabaseConnection) -> Result<Option<i64>, DbErr> {
+    let backfill = backfill::Entity::find_by_id(EVENT_XDR_BACKFILL)
+        .one(database)
+        .await?;
+
+    Ok(backfill
+        .filter(|backfill| backfill.position < backfill.end_position)
+        .map(|backfill| backfill.end_position))
+}
+
+/// Soroban RPC event id: the total order id of the operation, Soroban
+/// transactions having a single one, and the index of the event in its
+/// transaction
+fn event_id(ledger_sequence: i32, transaction_order: i32, index: i32) -> String {
+    format!(
+        "{:019}-{:010}",
+        operation_toid(ledger_sequence, transaction_order, 0),
+        index
+    )
+}
+
+/// Ledger sequence, transaction order and index in the transaction of an
+/// event id
+fn parse_event_id(id: &str) -> Option<(i32, i32, i32)> {
+    let (toid, index) = id.split_once('-')?;
+    let toid: i64 = toid.parse().ok()?;
+    let index: i32 = index.parse().ok()?;
+    if toid < 0 || index < 0 {
+        return None;
+    }
+
+    let (ledger_sequence, transaction_order, _) = parse_operation_toid(toid);
+    Some((ledger_sequence, transaction_order, index))
+}
+
+/// Events following the event of a cursor, in ledger, transaction and then
+/// emission order
+async fn after_event(
+    database: &DatabaseConnection,
+    (ledger_sequence, transaction_order, index): (i32, i32, i32),
+) -> Result<Condition, DbErr> {
+    let position = Expr::tuple([
+        transaction::Column::LedgerSequence.into_simple_expr(),
+        transaction::Column::ApplicationOrder.into_simple_expr(),
+    ]);
+    let mut after = Condition::any().add(position.gt(Expr::tuple([
+        ledger_sequence.into(),
+        transaction_order.into(),
+    ])));
+
+    // Events of a transaction are stored in emission order
+    let cursor_event = event::Entity::find()
+        .join(JoinType::InnerJoin, event::Relation::Transaction.def())
+        .filter(transaction::Column::LedgerSequence.eq(ledger_sequence))
+        .filter(transaction::Column::ApplicationOrder.eq(transaction_order))
+        .order_by_asc(event::Column::Id)
+        .offset(index as u64)
+        .one(database)
+        .await?;
+    if let Some(cursor_event) = cursor_event {
+        after = after.add(
+            Condition::all()
+                .add(event::Column::TransactionId.eq(cursor_event.transaction_id))
+                .add(event::Column::Id.gt(cursor_event.id)),
+        );
+    }
+
+    Ok(after)
+}
+
+/// Index of the events in their transaction, keyed by event id
+async fn event_indexes(
+    database: &DatabaseConnection,
+    events: &[(event::Model, Option<transaction::Model>)],
+) -> Result<HashMap<i32, i32>, DbErr> {
+    let transaction_ids: HashSet<&str> = events
+        .iter()
+        .map(|(event, _)| event.transaction_id.as_str())
+        .collect();
+    if transaction_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let transaction_events = event::Entity::find()
+        .select_only()
+        .column(event::Column::Id)
+        .column(event::Column::TransactionId)
+        .filter(event::Column::TransactionId.is_in(transaction_ids))
+        .order_by_asc(event::Column::Id)
+        .into_tuple::<(i32, String)>()
+        .all(database)
+        .await?;
+
+    let mut counts: HashMap<String, i32> = HashMap::new();
+    let mut indexes = HashMap::new();
+    for (id, transaction_id) in transaction_events {
+        let count = counts.entry(transaction_id).or_default();
+        indexes.insert(id, *count);
+        *count += 1;
+    }
+
+    Ok(indexes)
+}
+
+fn event_info(
+    event: event::Model,
+    transaction: Option<transaction::Model>,
+    index: Option<i32>,
+) -> Value {
+    let ledger = transaction
+        .as_ref()
+        .map(|transaction| transaction.ledger_sequence);
+    let id = match (transaction, index) {
+        (Some(transaction), Some(index)) => Some(event_id(
+            transaction.ledger_sequence,
+            transaction.application_order,
+            index,
+        )),
+        _ => None,
+    };
+    let ledger_closed_at = event.closed_at.map(|closed_at| closed_at.to_rfc3339());
+
+    json!({
+        "type": event.r#type.to_lowercase(),
+        "ledger": ledger,
+        "ledgerClosedAt": ledger_closed_at,
+        "contractId": event.contract_id,
+        "id": id,
+        "pagingToken": id,
+        "topic": event.raw_topics,
+        "value": event.raw_value,
+        // Only events of successful transactions are ingested
+        "inSuccessfulContractCall": true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_id_matches_soroban_rpc() {
+        assert_eq!(event_id(1, 1, 0), "0000000004294971392-0000000000");
+        assert_eq!(
+            parse_event_id("0000000004294971392-0000000002"),
+            Some((1, 1, 2))
+        );
+    }
+
+    #[test]
+    fn parse_event_id_rejects_invalid_ids() {
+        assert_eq!(parse_event_id("42"), None);
+        assert_eq!(parse_event_id("-1-0"), None);
+        assert_eq!(parse_event_id("0000000004294971392-x"), None);
+    }
+}
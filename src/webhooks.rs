@@ -0,0 +1,701 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use base64::{engine::general_purpose, Engine};
+use clap::Subcommand;
+use hmac::{Hmac, Mac};
+use hyper::client::connect::dns::Name;
+use log::{error, info, warn};
+use prometheus::{IntCounterVec, Opts, Registry};
+use quasar_entities::{event, webhook, webhook_dead_letter};
+use reqwest::{
+    dns::{Addrs, Resolve, Resolving},
+    header::CONTENT_TYPE,
+    redirect::Policy,
+    Client, Url,
+};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tokio::{
+    net::lookup_host,
+    sync::{broadcast::error::RecvError, watch, Semaphore},
+    task::JoinHandle,
+    time,
+};
+
+use crate::{
+    configuration::{WebhookEndpoint, Webhooks},
+    databases::QuasarDatabase,
+    notifications::{event_json, EventPredicate, Notification, Notifier},
+};
+
+/// Header carrying the hex encoded HMAC-SHA256 of the payload
+const SIGNATURE_HEADER: &str = "X-Quasar-Signature";
+/// Events fetched at once for a webhook
+const PAGE_SIZE: u64 = 100;
+/// Dead letters listed by the command line
+const DEAD_LETTERS_LIMIT: u64 = 50;
+/// Prefix of the secrets encrypted with the configured key
+const SEALED_PREFIX: &str = "aes256gcm:";
+/// Size of the AES-GCM nonce stored in front of an encrypted secret
+const NONCE_SIZE: usize = 12;
+
+#[derive(Subcommand, Debug)]
+pub(super) enum WebhookCommand {
+    /// Register a URL receiving the events matching the filters, payloads
+    /// are signed with the secret
+    Create {
+        url: String,
+        secret: String,
+        #[arg(long)]
+        contract_id: Option<String>,
+        #[arg(long)]
+        topic: Option<String>,
+        #[arg(long)]
+        r#type: Option<String>,
+    },
+    /// List the webhooks and the number of their dead letters
+    List,
+    /// Remove a webhook along with its dead letters
+    Delete { id: i32 },
+    /// Show the latest deliveries that failed after all retries
+    DeadLetters {
+        #[arg(long)]
+        webhook_id: Option<i32>,
+    },
+}
+
+/// Encrypts the webhook secrets with the key of the configuration before
+/// they are stored, secrets stored before a key was configured are read as
+/// plaintext
+struct Secrets {
+    cipher: Option<Aes256Gcm>,
+}
+
+impl Secrets {
+    fn new(configuration: &Webhooks) -> Result<Self, String> {
+        let cipher = match &configuration.secret_key {
+            Some(key) => {
+                let key = general_purpose::STANDARD
+                    .decode(key)
+                    .map_err(|err| format!("invalid secret key: {}", err))?;
+                if key.len() != 32 {
+                    return Err("secret key must be 32 bytes".to_string());
+                }
+                Some(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+            }
+            None => None,
+        };
+
+        Ok(Self { cipher })
+    }
+
+    /// Secret as stored in the database
+    fn seal(&self, secret: &str) -> String {
+        let cipher = match &self.cipher {
+            Some(cipher) => cipher,
+            None => return secret.to_string(),
+        };
+
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, secret.as_bytes())
+            .expect("AES-GCM encrypts secrets of any size");
+        let sealed = [nonce.as_slice(), &ciphertext].concat();
+        format!(
+            "{}{}",
+            SEALED_PREFIX,
+            general_purpose::STANDARD.encode(sealed)
+        )
+    }
+
+    /// Secret used to sign the payloads from its stored value
+    fn open(&self, stored: &str) -> Result<String, String> {
+        let sealed = match stored.strip_prefix(SEALED_PREFIX) {
+            Some(sealed) => sealed,
+            None => return Ok(stored.to_string()),
+        };
+        let cipher = self
+            .cipher
+            .as_ref()
+            .ok_or("secret is encrypted but no secret key is configured")?;
+
+        let sealed = general_purpose::STANDARD
+            .decode(sealed)
+            .map_err(|err| format!("invalid encrypted secret: {}", err))?;
+        if sealed.len() < NONCE_SIZE {
+            return Err("invalid encrypted secret".to_string());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_SIZE);
+        let secret = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "secret was encrypted with another key")?;
+
+        String::from_utf8(secret).map_err(|err| format!("invalid secret: {}", err))
+    }
+
+    fn is_sealed(&self, stored: &str) -> bool {
+        self.cipher.is_none() || stored.starts_with(SEALED_PREFIX)
+    }
+}
+
+struct Dispatcher {
+    database: DatabaseConnection,
+    client: Client,
+    secrets: Secrets,
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_retry_duration: Duration,
+    /// Bounds the requests in flight across all webhooks
+    permits: Semaphore,
+    deliveries: IntCounterVec,
+}
+
+pub(super) fn start_webhooks(
+    database: QuasarDatabase,
+    configuration: Webhooks,
+    registry: Registry,
+    notifier: Notifier,
+) {
+    let mut notifications = notifier.subscribe();
+
+    // Redirects could lead to internal addresses, so they are not followed
+    let client = Client::builder()
+        .timeout(Duration::from_secs(configuration.timeout))
+        .dns_resolver(Arc::new(PublicResolver))
+        .redirect(Policy::none())
+        .build()
+        .expect("Failed to build webhook HTTP client");
+    let secrets = Secrets::new(&configuration).expect("Failed to load webhook secret key");
+    let dispatcher = Arc::new(Dispatcher {
+        database: database.as_inner().clone(),
+        client,
+        secrets,
+        max_attempts: configuration.max_attempts.max(1),
+        initial_backoff: Duration::from_millis(configuration.initial_backoff),
+        max_retry_duration: Duration::from_secs(configuration.max_retry_duration),
+        permits: Semaphore::new(configuration.max_concurrent_deliveries.max(1)),
+        deliveries: setup_metrics(&registry),
+    });
+
+    tokio::spawn(async move {
+        if let Err(err) = seal_secrets(&dispatcher.database, &dispatcher.secrets).await {
+            error!("Failed to encrypt webhook secrets: {}", err);
+        }
+        if let Err(err) = register_endpoints(
+            &dispatcher.database,
+            &dispatcher.secrets,
+            configuration.endpoints,
+        )
+        .await
+        {
+            error!("Failed to register configured webhooks: {}", err);
+        }
+
+        let (wake, _) = watch::channel(());
+        let mut workers: HashMap<i32, JoinHandle<()>> = HashMap::new();
+
+        loop {
+            // Pick up webhooks created or deleted since the previous ledger
+            let webhooks = load_webhooks(&dispatcher.database).await;
+            workers.retain(|id, worker| {
+                let keep = webhooks.iter().any(|webhook| webhook.id == *id);
+                if !keep {
+                    worker.abort();
+                }
+                keep
+            });
+            for webhook in webhooks {
+                workers.entry(webhook.id).or_insert_with(|| {
+                    tokio::spawn(dispatcher.clone().run(webhook.id, wake.subscribe()))
+                });
+            }
+            wake.send_replace(());
+
+            // Events are read back from the database, so notifications
+            // skipped while lagging behind are harmless
+            loop {
+                match notifications.recv().await {
+                    Ok(Notification::Ledger(_)) | Err(RecvError::Lagged(_)) => break,
+                    Ok(_) => {}
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        }
+    });
+}
+
+fn setup_metrics(registry: &Registry) -> IntCounterVec {
+    let deliveries = IntCounterVec::new(
+        Opts::new("webhook_deliveries", "Number of webhook delivery attempts"),
+        &["webhook", "status"],
+    )
+    .unwrap();
+    registry
+        .register(Box::new(deliveries.clone()))
+        .expect("Failed to register counter");
+    deliveries
+}
+
+/// Encrypts the secrets stored in plaintext once a secret key is configured
+async fn seal_secrets(database: &DatabaseConnection, secrets: &Secrets) -> Result<(), DbErr> {
+    for webhook in webhook::Entity::find().all(database).await? {
+        if secrets.is_sealed(&webhook.secret) {
+            continue;
+        }
+
+        let secret = secrets.seal(&webhook.secret);
+        let mut webhook: webhook::ActiveModel = webhook.into();
+        webhook.secret = Set(secret);
+        webhook.update(database).await?;
+    }
+
+    Ok(())
+}
+
+async fn register_endpoints(
+    database: &DatabaseConnection,
+    secrets: &Secrets,
+    endpoints: Vec<WebhookEndpoint>,
+) -> Result<(), DbErr> {
+    for endpoint in endpoints {
+        if let Err(err) = check_target(&endpoint.url).await {
+            error!("Skipping webhook {:?}: {}", endpoint.url, err);
+            continue;
+        }
+
+        let existing = webhook::Entity::find()
+            .filter(webhook::Column::Url.eq(&endpoint.url))
+            .one(database)
+            .await?;
+
+        let mut webhook: webhook::ActiveModel = match existing {
+            Some(existing) => existing.into(),
+            None => Default::default(),
+        };
+        webhook.url = Set(endpoint.url);
+        webhook.secret = Set(secrets.seal(&endpoint.secret));
+        webhook.contract_id = Set(endpoint.contract_id);
+        webhook.topic = Set(endpoint.topic);
+        webhook.r#type = Set(endpoint.r#type);
+        let webhook = webhook.save(database).await?;
+
+        info!("Registered webhook {:?}", webhook.url);
+    }
+
+    Ok(())
+}
+
+async fn load_webhooks(database: &DatabaseConnection) -> Vec<webhook::Model> {
+    match webhook::Entity::find().all(database).await {
+        Ok(webhooks) => webhooks,
+        Err(err) => {
+            error!("Failed to load webhooks: {}", err);
+            Vec::new()
+        }
+    }
+}
+
+/// Whether an address can be reached from the internet, webhooks must not
+/// be used to send requests to loopback, link-local or private networks
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || first == 0
+                // Shared address space of carrier-grade NATs, 100.64.0.0/10
+                || (first == 100 && second & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                // Unique local fc00::/7 and link-local fe80::/10 addresses
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Checks that a webhook URL is HTTP(S) and that its host, when it is an IP
+/// address, is public
+fn check_url(url: &str) -> Result<Url, String> {
+    let url = Url::parse(url).map_err(|err| format!("invalid URL: {}", err))?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("URL must be HTTP(S)".to_string());
+    }
+
+    let host = url.host_str().ok_or("URL must have a host")?;
+    // IPv6 hosts are enclosed in brackets
+    if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse() {
+        if !is_public(ip) {
+            return Err(format!("{} is not a public address", ip));
+        }
+    }
+
+    Ok(url)
+}
+
+/// Checks a webhook URL when it is registered, resolving its host to make
+/// sure every address it points to is public
+async fn check_target(url: &str) -> Result<Url, String> {
+    let url = check_url(url)?;
+
+    let host = url.host_str().unwrap_or_default();
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addresses = lookup_host((host.trim_start_matches('[').trim_end_matches(']'), port))
+        .await
+        .map_err(|err| format!("failed to resolve {}: {}", host, err))?;
+
+    for address in addresses {
+        if !is_public(address.ip()) {
+            return Err(format!("{} resolves to {}", host, address.ip()));
+        }
+    }
+
+    Ok(url)
+}
+
+/// Resolver of the webhook client, refusing hosts resolving to addresses
+/// that are not public in case DNS changed since the webhook was registered
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = lookup_host((name.as_str(), 0)).await?.collect();
+
+            if let Some(address) = addresses.iter().find(|address| !is_public(address.ip())) {
+                return Err(format!("{} resolves to {}", name.as_str(), address.ip()).into());
+            }
+
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any size");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn payload(webhook: &webhook::Model, event: &event::Model) -> Value {
+    json!({
+        "webhook_id": webhook.id,
+        "event": event_json(event),
+    })
+}
+
+impl Dispatcher {
+    /// Delivers the events of a webhook in order, then waits for the next
+    /// ledger to look for new ones
+    async fn run(self: Arc<Self>, webhook_id: i32, mut wake: watch::Receiver<()>) {
+        loop {
+            match self.deliver_pending(webhook_id).await {
+                // A full page was delivered, more events may be pending
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(err) => error!(
+                    "Failed to deliver events to webhook {}: {}",
+                    webhook_id, err
+                ),
+            }
+
+            if wake.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Delivers the next page of events after the cursor of the webhook,
+    /// returns whether the page was full
+    async fn deliver_pending(&self, webhook_id: i32) -> Result<bool, DbErr> {
+        let webhook = match webhook::Entity::find_by_id(webhook_id)
+            .one(&self.database)
+            .await?
+        {
+            Some(webhook) => webhook,
+            None => return Ok(false),
+        };
+
+        // Events stay pending until the secret can be read again
+        let secret = match self.secrets.open(&webhook.secret) {
+            Ok(secret) => secret,
+            Err(err) => {
+                error!("Not delivering events to webhook {}: {}", webhook_id, err);
+                return Ok(false);
+            }
+        };
+
+        let last_event_id = match webhook.last_event_id {
+            Some(last_event_id) => last_event_id,
+            // New webhooks start with the events ingested from now on
+            None => {
+                let latest: Option<Option<i32>> = event::Entity::find()
+                    .select_only()
+                    .column_as(event::Column::Id.max(), "id")
+                    .into_tuple()
+                    .one(&self.database)
+                    .await?;
+                self.advance(&webhook, latest.flatten().unwrap_or_default())
+                    .await?;
+                return Ok(false);
+            }
+        };
+
+        let predicate = EventPredicate {
+            contract_id: webhook.contract_id.clone(),
+            topic: webhook.topic.clone(),
+            r#type: webhook.r#type.clone(),
+        };
+        let events = event::Entity::find()
+            .filter(event::Column::Id.gt(last_event_id))
+            .filter(predicate.condition())
+            .order_by_asc(event::Column::Id)
+            .limit(PAGE_SIZE)
+            .all(&self.database)
+            .await?;
+
+        for event in &events {
+            self.deliver(&webhook, &secret, event).await;
+            self.advance(&webhook, event.id).await?;
+        }
+
+        Ok(events.len() as u64 == PAGE_SIZE)
+    }
+
+    async fn advance(&self, webhook: &webhook::Model, last_event_id: i32) -> Result<(), DbErr> {
+        webhook::Entity::update_many()
+            .col_expr(webhook::Column::LastEventId, Expr::value(last_event_id))
+            .filter(webhook::Column::Id.eq(webhook.id))
+            .exec(&self.database)
+            .await?;
+        Ok(())
+    }
+
+    /// Delivers an event with retries, moving it to the dead letters once the
+    /// attempts or the retry duration are exhausted
+    async fn deliver(&self, webhook: &webhook::Model, secret: &str, event: &event::Model) {
+        let payload = payload(webhook, event);
+        let body = payload.to_string();
+        let signature = format!("sha256={}", sign(secret, body.as_bytes()));
+        let label = webhook.id.to_string();
+
+        if let Err(err) = check_url(&webhook.url) {
+            warn!(
+                "Not delivering event {} to webhook {}: {}",
+                event.id, webhook.id, err
+            );
+            self.dead_letter(webhook, event, payload, err, 0).await;
+            return;
+        }
+
+        let started = Instant::now();
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            let permit = self
+                .permits
+                .acquire()
+                .await
+                .expect("Delivery semaphore is never closed");
+            let response = self
+                .client
+                .post(&webhook.url)
+                .header(CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, &signature)
+                .body(body.clone())
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            drop(permit);
+
+            let err = match response {
+                Ok(_) => {
+                    self.deliveries
+                        .with_label_values(&[&label, "delivered"])
+                        .inc();
+                    return;
+                }
+                Err(err) => err,
+            };
+
+            self.deliveries.with_label_values(&[&label, "failed"]).inc();
+
+            let backoff = self
+                .initial_backoff
+                .saturating_mul(2u32.saturating_pow(attempt - 1));
+
+            // The later events of the webhook wait for this one, so retries
+            // are bounded in time as well
+            if attempt >= self.max_attempts || started.elapsed() + backoff > self.max_retry_duration
+            {
+                warn!(
+                    "Giving up delivering event {} to webhook {}: {}",
+                    event.id, webhook.id, err
+                );
+                self.dead_letter(webhook, event, payload, err.to_string(), attempt)
+                    .await;
+                return;
+            }
+
+            time::sleep(backoff).await;
+        }
+    }
+
+    async fn dead_letter(
+        &self,
+        webhook: &webhook::Model,
+        event: &event::Model,
+        payload: Value,
+        error: String,
+        attempts: u32,
+    ) {
+        let dead_letter = webhook_dead_letter::ActiveModel {
+            webhook_id: Set(webhook.id),
+            event_id: Set(event.id),
+            payload: Set(payload),
+            error: Set(error),
+            attempts: Set(attempts as i32),
+            ..Default::default()
+        };
+
+        self.deliveries
+            .with_label_values(&[&webhook.id.to_string(), "dead_lettered"])
+            .inc();
+
+        if let Err(err) = dead_letter.insert(&self.database).await {
+            error!("Failed to store webhook dead letter: {}", err);
+        }
+    }
+}
+
+pub(super) async fn run_webhook_command(
+    database: &QuasarDatabase,
+    configuration: &Webhooks,
+    command: WebhookCommand,
+) {
+    let database = database.as_inner();
+    let secrets = Secrets::new(configuration).expect("Failed to load webhook secret key");
+
+    match command {
+        WebhookCommand::Create {
+            url,
+            secret,
+            contract_id,
+            topic,
+            r#type,
+        } => {
+            if let Err(err) = check_target(&url).await {
+                println!("Invalid webhook URL {:?}: {}", url, err);
+                return;
+            }
+
+            if secret.is_empty() {
+                println!("Webhook secret must not be empty");
+                return;
+            }
+
+            let webhook = webhook::ActiveModel {
+                url: Set(url),
+                secret: Set(secrets.seal(&secret)),
+                contract_id: Set(contract_id),
+                topic: Set(topic),
+                r#type: Set(r#type),
+                ..Default::default()
+            };
+            let webhook = webhook
+                .insert(database)
+                .await
+                .expect("Failed to create webhook");
+
+            println!("Created webhook {} for {}", webhook.id, webhook.url);
+        }
+        WebhookCommand::List => {
+            let webhooks = webhook::Entity::find()
+                .order_by_asc(webhook::Column::Id)
+                .all(database)
+                .await
+                .expect("Failed to list webhooks");
+
+            for webhook in webhooks {
+                let dead_letters = webhook_dead_letter::Entity::find()
+                    .filter(webhook_dead_letter::Column::WebhookId.eq(webhook.id))
+                    .count(database)
+                    .await
+                    .expect("Failed to count dead letters");
+                println!(
+                    "{}\t{}\tcontract {}\ttopic {}\ttype {}\t{} dead letters",
+                    webhook.id,
+                    webhook.url,
+                    webhook.contract_id.as_deref().unwrap_or("*"),
+                    webhook.topic.as_deref().unwrap_or("*"),
+                    webhook.r#type.as_deref().unwrap_or("*"),
+                    dead_letters
+                );
+            }
+        }
+        WebhookCommand::Delete { id } => {
+            let result = webhook::Entity::delete_by_id(id)
+                .exec(database)
+                .await
+                .expect("Failed to delete webhook");
+
+            if result.rows_affected == 0 {
+                println!("No webhook {}", id);
+            } else {
+                println!("Deleted webhook {}", id);
+            }
+        }
+        WebhookCommand::DeadLetters { webhook_id } => {
+            let mut query =
+                webhook_dead_letter::Entity::find().order_by_desc(webhook_dead_letter::Column::Id);
+
+            if let Some(webhook_id) = webhook_id {
+                query = query.filter(webhook_dead_letter::Column::WebhookId.eq(webhook_id));
+            }
+
+            let dead_letters = query
+                .limit(DEAD_LETTERS_LIMIT)
+                .all(database)
+                .await
+                .expect("Failed to list dead letters");
+
+            for dead_letter in dead_letters {
+                println!(
+                    "webhook {}\tevent {}\t{} attempts\t{}\t{}",
+                    dead_letter.webhook_id,
+                    dead_letter.event_id,
+                    dead_letter.attempts,
+                    dead_letter.created_at,
+                    dead_letter.error
+                );
+            }
+        }
+    }
+}